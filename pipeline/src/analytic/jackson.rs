@@ -0,0 +1,97 @@
+use super::{is_exponential, solve, QueueMetrics, Station};
+use crate::distr::Moments;
+
+/// An open network of stations with exponential handling times, unlimited buffers
+/// and probabilistic routing.
+#[derive(Debug, Clone)]
+pub struct JacksonNetwork {
+    pub stations: Vec<Station>,
+    /// Rate of the Poisson arrivals from outside to each station.
+    pub external_arrival_rates: Vec<f64>,
+    /// `routing[i][j]` is the probability that a request goes from station `i` to station `j`,
+    /// the rest of the row is the probability of leaving the network.
+    pub routing: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JacksonMetrics {
+    /// Total arrival rate to each station, including the requests routed back.
+    pub arrival_rates: Vec<f64>,
+    pub nodes: Vec<QueueMetrics>,
+    pub mean_number_in_system: f64,
+    /// Mean time from entering the network until leaving it.
+    pub mean_sojourn_time: f64,
+    pub throughput: f64,
+}
+
+impl JacksonNetwork {
+    /// A line of stations fed by renewal arrivals with the distribution of the times between
+    /// them, `None` if the arrivals aren't Poisson.
+    pub fn line<D: Moments>(arrival_distr: &D, stations: Vec<Station>) -> Option<Self> {
+        if stations.is_empty() || !is_exponential(arrival_distr.scv()) {
+            return None;
+        }
+
+        let n = stations.len();
+        let mut external_arrival_rates = vec![0.0; n];
+        external_arrival_rates[0] = 1.0 / arrival_distr.mean();
+        let routing = (0..n)
+            .map(|i| (0..n).map(|j| if j == i + 1 { 1.0 } else { 0.0 }).collect())
+            .collect();
+
+        Some(Self {
+            stations,
+            external_arrival_rates,
+            routing,
+        })
+    }
+
+    /// Solves the traffic equations `λ = γ + Pᵀλ`, `None` if they have no solution.
+    pub fn arrival_rates(&self) -> Option<Vec<f64>> {
+        let n = self.stations.len();
+        let a = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let identity = if i == j { 1.0 } else { 0.0 };
+                        identity - self.routing[j][i]
+                    })
+                    .collect()
+            })
+            .collect();
+
+        solve(a, self.external_arrival_rates.clone())
+    }
+
+    /// Product-form metrics, `None` if a station is unstable, has a limited buffer
+    /// or non-exponential handling times.
+    pub fn metrics(&self) -> Option<JacksonMetrics> {
+        let arrival_rates = self.arrival_rates()?;
+        let nodes = self
+            .stations
+            .iter()
+            .zip(&arrival_rates)
+            .map(|(station, rate)| {
+                if !station.is_markovian() || station.capacity.is_some() {
+                    return None;
+                }
+
+                station.metrics(*rate)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let throughput = self.external_arrival_rates.iter().sum::<f64>();
+        let mean_number_in_system = nodes
+            .iter()
+            .map(|node| node.mean_number_in_system)
+            .sum::<f64>();
+
+        Some(JacksonMetrics {
+            arrival_rates,
+            nodes,
+            mean_number_in_system,
+            mean_sojourn_time: mean_number_in_system / throughput,
+            throughput,
+        })
+    }
+}
@@ -0,0 +1,38 @@
+/// Solves `a * x = b` by the Gaussian elimination with partial pivoting,
+/// `None` if the matrix is singular.
+pub(crate) fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+
+    for column in 0..n {
+        let pivot =
+            (column..n).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+
+        for row in column + 1..n {
+            let factor = a[row][column] / a[column][column];
+            if factor == 0.0 {
+                continue;
+            }
+
+            let (upper, lower) = a.split_at_mut(row);
+            for (value, pivot_value) in lower[0][column..].iter_mut().zip(&upper[column][column..])
+            {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    Some(x)
+}
@@ -0,0 +1,338 @@
+//! Closed-form results of the queueing theory to check the simulations against.
+
+mod jackson;
+mod linear;
+mod report;
+mod tandem;
+
+pub use self::jackson::{JacksonMetrics, JacksonNetwork};
+pub use self::report::{Report, ReportRow};
+pub use self::tandem::{TandemMetrics, TandemNetwork, TandemNodeMetrics};
+
+pub(crate) use self::linear::solve;
+
+use crate::{distr::Moments, PipelineParams};
+
+/// Steady-state metrics of a station.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct QueueMetrics {
+    /// Fraction of time an individual server is busy.
+    pub utilization: f64,
+    /// Probability that the station is empty.
+    pub idle_probability: f64,
+    /// Probability that an arriving request is rejected because the station is full.
+    pub loss_probability: f64,
+    /// Probability that an accepted request has to wait for a server.
+    pub waiting_probability: f64,
+    pub mean_number_in_system: f64,
+    pub mean_number_in_queue: f64,
+    pub mean_sojourn_time: f64,
+    pub mean_waiting_time: f64,
+    /// Rate of the accepted, and so of the handled, requests.
+    pub throughput: f64,
+}
+
+/// Parameters of a station the formulas depend on.
+#[derive(Debug, Clone, Copy)]
+pub struct Station {
+    pub servers: usize,
+    /// Maximum number of requests in the station including the ones being handled,
+    /// `None` if the buffer is unlimited.
+    pub capacity: Option<usize>,
+    pub service_mean: f64,
+    pub service_variance: f64,
+}
+
+impl Station {
+    pub fn new<D: Moments>(service_distr: &D, servers: usize, capacity: Option<usize>) -> Self {
+        Self {
+            servers,
+            capacity,
+            service_mean: service_distr.mean(),
+            service_variance: service_distr.variance(),
+        }
+    }
+
+    /// Whether the handling times are exponential, judging by their moments.
+    pub fn is_markovian(&self) -> bool {
+        is_exponential(self.service_variance / self.service_mean.powi(2))
+    }
+
+    /// Metrics under Poisson arrivals, `None` if the station is unstable or there's no closed form
+    /// for it, e.g. for non-exponential handling times with several servers or a limited buffer.
+    pub fn metrics(&self, arrival_rate: f64) -> Option<QueueMetrics> {
+        let service_rate = 1.0 / self.service_mean;
+
+        match (self.is_markovian(), self.servers, self.capacity) {
+            (true, servers, Some(capacity)) => mmck(arrival_rate, service_rate, servers, capacity),
+            (true, servers, None) => mmc(arrival_rate, service_rate, servers),
+            (false, 1, None) => mg1(arrival_rate, self.service_mean, self.service_variance),
+            _ => None,
+        }
+    }
+}
+
+impl<D> PipelineParams<D>
+where
+    D: Moments,
+{
+    /// Rate of the arrivals, the formulas assume they are Poisson.
+    pub fn arrival_rate(&self) -> f64 {
+        1.0 / self.arrival_distr.mean()
+    }
+}
+
+pub fn mm1(arrival_rate: f64, service_rate: f64) -> Option<QueueMetrics> {
+    mmc(arrival_rate, service_rate, 1)
+}
+
+pub fn mm1k(arrival_rate: f64, service_rate: f64, capacity: usize) -> Option<QueueMetrics> {
+    mmck(arrival_rate, service_rate, 1, capacity)
+}
+
+/// M/M/c with an unlimited buffer, `None` if the queue grows without bound.
+pub fn mmc(arrival_rate: f64, service_rate: f64, servers: usize) -> Option<QueueMetrics> {
+    let offered_load = arrival_rate / service_rate;
+    let utilization = offered_load / servers as f64;
+
+    if servers == 0 || utilization >= 1.0 {
+        return None;
+    }
+
+    let waiting_probability = erlang_c(offered_load, servers);
+    let mean_number_in_queue = waiting_probability * utilization / (1.0 - utilization);
+    let mean_waiting_time = mean_number_in_queue / arrival_rate;
+    let mean_sojourn_time = mean_waiting_time + 1.0 / service_rate;
+
+    // P(n >= c) = C, and the states below `c` are proportional to `a^n / n!`.
+    let free_server_states_weight = (0..servers).map(|n| term(offered_load, n)).sum::<f64>();
+    let idle_probability = (1.0 - waiting_probability) / free_server_states_weight;
+
+    Some(QueueMetrics {
+        utilization,
+        idle_probability,
+        loss_probability: 0.0,
+        waiting_probability,
+        mean_number_in_system: arrival_rate * mean_sojourn_time,
+        mean_number_in_queue,
+        mean_sojourn_time,
+        mean_waiting_time,
+        throughput: arrival_rate,
+    })
+}
+
+/// M/M/c/K, `capacity` counts the requests being handled too.
+pub fn mmck(
+    arrival_rate: f64,
+    service_rate: f64,
+    servers: usize,
+    capacity: usize,
+) -> Option<QueueMetrics> {
+    if servers == 0 || capacity < servers {
+        return None;
+    }
+
+    let offered_load = arrival_rate / service_rate;
+    let weights = (0..=capacity)
+        .map(|n| {
+            if n <= servers {
+                term(offered_load, n)
+            } else {
+                term(offered_load, servers)
+                    * (offered_load / servers as f64).powi((n - servers) as i32)
+            }
+        })
+        .collect::<Vec<_>>();
+    let total = weights.iter().sum::<f64>();
+    let probabilities = weights
+        .iter()
+        .map(|weight| weight / total)
+        .collect::<Vec<_>>();
+
+    let loss_probability = probabilities[capacity];
+    let throughput = arrival_rate * (1.0 - loss_probability);
+    let mean_number_in_system = probabilities
+        .iter()
+        .enumerate()
+        .map(|(n, p)| n as f64 * p)
+        .sum::<f64>();
+    let mean_number_in_queue = probabilities
+        .iter()
+        .enumerate()
+        .skip(servers)
+        .map(|(n, p)| (n - servers) as f64 * p)
+        .sum::<f64>();
+    let waiting_probability =
+        probabilities[servers..capacity].iter().sum::<f64>() / (1.0 - loss_probability);
+
+    Some(QueueMetrics {
+        utilization: throughput / (servers as f64 * service_rate),
+        idle_probability: probabilities[0],
+        loss_probability,
+        waiting_probability,
+        mean_number_in_system,
+        mean_number_in_queue,
+        mean_sojourn_time: mean_number_in_system / throughput,
+        mean_waiting_time: mean_number_in_queue / throughput,
+        throughput,
+    })
+}
+
+/// M/G/1 by the Pollaczek–Khinchine formula, `None` if the queue grows without bound.
+pub fn mg1(arrival_rate: f64, service_mean: f64, service_variance: f64) -> Option<QueueMetrics> {
+    let utilization = arrival_rate * service_mean;
+
+    if utilization >= 1.0 {
+        return None;
+    }
+
+    let second_moment = service_variance + service_mean.powi(2);
+    let mean_waiting_time = arrival_rate * second_moment / (2.0 * (1.0 - utilization));
+    let mean_sojourn_time = mean_waiting_time + service_mean;
+
+    Some(QueueMetrics {
+        utilization,
+        idle_probability: 1.0 - utilization,
+        loss_probability: 0.0,
+        waiting_probability: utilization,
+        mean_number_in_system: arrival_rate * mean_sojourn_time,
+        mean_number_in_queue: arrival_rate * mean_waiting_time,
+        mean_sojourn_time,
+        mean_waiting_time,
+        throughput: arrival_rate,
+    })
+}
+
+/// Probability that a request is lost in M/M/c/c with the offered load `arrival_rate / service_rate`.
+pub fn erlang_b(offered_load: f64, servers: usize) -> f64 {
+    (1..=servers).fold(1.0, |blocking, n| {
+        offered_load * blocking / (n as f64 + offered_load * blocking)
+    })
+}
+
+/// Probability that a request waits in M/M/c, it's `1` when the queue is unstable.
+pub fn erlang_c(offered_load: f64, servers: usize) -> f64 {
+    if offered_load >= servers as f64 {
+        return 1.0;
+    }
+
+    let blocking = erlang_b(offered_load, servers);
+    servers as f64 * blocking / (servers as f64 - offered_load * (1.0 - blocking))
+}
+
+/// Whether a distribution with the squared coefficient of variation may be exponential.
+fn is_exponential(scv: f64) -> bool {
+    (scv - 1.0).abs() < 1e-4
+}
+
+/// `a^n / n!`
+fn term(offered_load: f64, n: usize) -> f64 {
+    (1..=n).fold(1.0, |term, i| term * offered_load / i as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn erlang_formulas() {
+        assert_close(erlang_b(1.0, 2), 0.2);
+        assert_close(erlang_c(1.0, 2), 1.0 / 3.0);
+        assert_close(erlang_c(2.0, 2), 1.0);
+    }
+
+    #[test]
+    fn mm1() {
+        let metrics = super::mm1(1.0, 2.0).unwrap();
+
+        assert_close(metrics.utilization, 0.5);
+        assert_close(metrics.idle_probability, 0.5);
+        assert_close(metrics.mean_number_in_system, 1.0);
+        assert_close(metrics.mean_number_in_queue, 0.5);
+        assert_close(metrics.mean_sojourn_time, 1.0);
+        assert_close(metrics.mean_waiting_time, 0.5);
+        assert!(super::mm1(2.0, 2.0).is_none());
+    }
+
+    #[test]
+    fn mmc() {
+        let metrics = super::mmc(1.0, 1.0, 2).unwrap();
+
+        assert_close(metrics.utilization, 0.5);
+        assert_close(metrics.idle_probability, 1.0 / 3.0);
+        assert_close(metrics.waiting_probability, 1.0 / 3.0);
+        assert_close(metrics.mean_number_in_queue, 1.0 / 3.0);
+        assert_close(metrics.mean_waiting_time, 1.0 / 3.0);
+        assert_close(metrics.mean_sojourn_time, 4.0 / 3.0);
+    }
+
+    #[test]
+    fn mmck() {
+        // a = 2, the weights of 0..=3 requests are 1, 2, 2, 2
+        let metrics = super::mmck(2.0, 1.0, 2, 3).unwrap();
+
+        assert_close(metrics.idle_probability, 1.0 / 7.0);
+        assert_close(metrics.loss_probability, 2.0 / 7.0);
+        assert_close(metrics.throughput, 10.0 / 7.0);
+        assert_close(metrics.utilization, 5.0 / 7.0);
+        assert_close(metrics.mean_number_in_system, 12.0 / 7.0);
+        assert_close(metrics.mean_number_in_queue, 2.0 / 7.0);
+        assert_close(metrics.waiting_probability, 0.4);
+        assert_close(metrics.mean_sojourn_time, 1.2);
+    }
+
+    #[test]
+    fn mm1k() {
+        // the states 0, 1 and 2 are equally likely
+        let metrics = super::mm1k(1.0, 1.0, 2).unwrap();
+
+        assert_close(metrics.loss_probability, 1.0 / 3.0);
+        assert_close(metrics.mean_number_in_system, 1.0);
+        assert_close(metrics.throughput, 2.0 / 3.0);
+        assert_close(metrics.waiting_probability, 0.5);
+    }
+
+    #[test]
+    fn mg1_pollaczek_khinchine() {
+        // M/D/1 waits half as long as M/M/1
+        let metrics = super::mg1(0.5, 1.0, 0.0).unwrap();
+        assert_close(metrics.mean_waiting_time, 0.5);
+        assert_close(metrics.mean_sojourn_time, 1.5);
+        assert_close(metrics.mean_number_in_queue, 0.25);
+
+        let exponential = super::mg1(0.5, 1.0, 1.0).unwrap();
+        let markovian = super::mm1(0.5, 1.0).unwrap();
+        assert_close(exponential.mean_waiting_time, markovian.mean_waiting_time);
+        assert_close(
+            exponential.mean_number_in_system,
+            markovian.mean_number_in_system,
+        );
+
+        assert!(super::mg1(1.0, 1.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn jackson_line() {
+        use crate::distr::{DeterministicDistr, ExpDistr};
+
+        let stations = vec![
+            Station::new(&ExpDistr::new(1.0).unwrap(), 1, None),
+            Station::new(&ExpDistr::new(0.5).unwrap(), 1, None),
+        ];
+        // two M/M/1 queues one after the other, the sojourn times add up
+        let line = JacksonNetwork::line(&ExpDistr::new(2.0).unwrap(), stations.clone()).unwrap();
+        let metrics = line.metrics().unwrap();
+        assert_close(metrics.mean_sojourn_time, 2.0 + 2.0 / 3.0);
+        assert_close(metrics.throughput, 0.5);
+
+        let deterministic = DeterministicDistr::new(2.0).unwrap();
+        assert!(JacksonNetwork::line(&deterministic, stations).is_none());
+    }
+}
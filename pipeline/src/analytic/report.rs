@@ -0,0 +1,176 @@
+use super::{JacksonMetrics, QueueMetrics, TandemMetrics};
+use crate::Statistics;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Theoretical and simulated values of the metrics side by side.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Report {
+    pub rows: Vec<ReportRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRow {
+    pub metric: String,
+    pub theory: f64,
+    /// `None` if the simulated value is undefined, e.g. nothing is handled.
+    pub simulation: Option<f64>,
+}
+
+impl ReportRow {
+    pub fn new(metric: impl Into<String>, theory: f64, simulation: Option<f64>) -> Self {
+        Self {
+            metric: metric.into(),
+            theory,
+            simulation,
+        }
+    }
+
+    /// `None` if the theoretical value is zero or the simulated one is undefined.
+    pub fn relative_error(&self) -> Option<f64> {
+        let simulation = self.simulation?;
+        (self.theory != 0.0).then(|| (simulation - self.theory).abs() / self.theory.abs())
+    }
+}
+
+impl Report {
+    /// Compares a pipeline consisting of the single station `node` with its theory.
+    pub fn station(theory: &QueueMetrics, stats: &Statistics, node: usize) -> Self {
+        let idle_probabilities = stats.get_nodes_idle_time_probabilities();
+        let busy_probabilities = stats.get_servers_busy_time_probabilities();
+
+        Self {
+            rows: vec![
+                ReportRow::new(
+                    "mean sojourn time",
+                    theory.mean_sojourn_time,
+                    stats.average_handling_time,
+                ),
+                ReportRow::new(
+                    "loss probability",
+                    theory.loss_probability,
+                    stats.probability_of_request_delay,
+                ),
+                ReportRow::new(
+                    "idle probability",
+                    theory.idle_probability,
+                    nth(&idle_probabilities, node),
+                ),
+                ReportRow::new(
+                    "utilization",
+                    theory.utilization,
+                    nth(&busy_probabilities, node).and_then(|servers| mean(&servers)),
+                ),
+                ReportRow::new("throughput", theory.throughput, stats.throughput()),
+            ],
+        }
+    }
+
+    /// Compares a pipeline built from the same network with its theory.
+    pub fn network(theory: &JacksonMetrics, stats: &Statistics) -> Self {
+        let idle_probabilities = stats.get_nodes_idle_time_probabilities();
+        let busy_probabilities = stats.get_servers_busy_time_probabilities();
+
+        let mut rows = vec![
+            ReportRow::new(
+                "mean sojourn time",
+                theory.mean_sojourn_time,
+                stats.average_handling_time,
+            ),
+            ReportRow::new("throughput", theory.throughput, stats.throughput()),
+        ];
+
+        for (i, node) in theory.nodes.iter().enumerate() {
+            rows.push(ReportRow::new(
+                format!("node {i} idle probability"),
+                node.idle_probability,
+                nth(&idle_probabilities, i),
+            ));
+            rows.push(ReportRow::new(
+                format!("node {i} utilization"),
+                node.utilization,
+                nth(&busy_probabilities, i).and_then(|servers| mean(&servers)),
+            ));
+        }
+
+        Self { rows }
+    }
+
+    /// Compares a pipeline built from the same chain of stations with its exact solution.
+    pub fn tandem(theory: &TandemMetrics, stats: &Statistics) -> Self {
+        let idle_probabilities = stats.get_nodes_idle_time_probabilities();
+        let blocked_probabilities = stats.get_nodes_blocked_time_probabilities();
+
+        let mut rows = vec![
+            ReportRow::new(
+                "delay probability",
+                theory.delay_probability,
+                stats.probability_of_request_delay,
+            ),
+            ReportRow::new(
+                "mean sojourn time",
+                theory.mean_sojourn_time,
+                stats.average_handling_time,
+            ),
+            ReportRow::new("throughput", theory.throughput, stats.throughput()),
+        ];
+
+        for (i, node) in theory.nodes.iter().enumerate() {
+            rows.push(ReportRow::new(
+                format!("node {i} idle probability"),
+                node.idle_probability,
+                nth(&idle_probabilities, i),
+            ));
+            rows.push(ReportRow::new(
+                format!("node {i} blocked probability"),
+                node.blocked_probability,
+                nth(&blocked_probabilities, i),
+            ));
+            rows.push(ReportRow::new(
+                format!("node {i} mean requests number"),
+                node.mean_number_in_system,
+                stats
+                    .nodes
+                    .get(i)
+                    .and_then(|node| node.average_requests_number),
+            ));
+        }
+
+        Self { rows }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<28} {:>12} {:>12} {:>10}",
+            "metric", "theory", "simulation", "error"
+        )?;
+
+        for row in &self.rows {
+            let simulation = row
+                .simulation
+                .map_or("-".to_owned(), |simulation| format!("{simulation:.5}"));
+            let error = row
+                .relative_error()
+                .map_or("-".to_owned(), |error| format!("{:.2}%", error * 100.0));
+
+            writeln!(
+                f,
+                "{:<28} {:>12.5} {:>12} {:>10}",
+                row.metric, row.theory, simulation, error
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn nth<T: Clone>(values: &Option<Vec<T>>, i: usize) -> Option<T> {
+    values.as_ref()?.get(i).cloned()
+}
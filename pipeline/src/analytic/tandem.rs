@@ -0,0 +1,368 @@
+use super::{is_exponential, solve, Station};
+use crate::distr::Moments;
+use std::collections::HashMap;
+
+/// Largest chain solved by the elimination, the bigger ones are solved by Gauss-Seidel iterations.
+const DIRECT_STATES: usize = 1000;
+const MAX_ITERATIONS: usize = 10_000;
+/// Largest change of a probability in an iteration the iterations stop at.
+const TOLERANCE: f64 = 1e-12;
+
+/// Stations in series with exponential handling times, limited buffers and blocking after service,
+/// under Poisson arrivals that are lost when the first station is full.
+#[derive(Debug, Clone)]
+pub struct TandemNetwork {
+    pub arrival_rate: f64,
+    /// Squared coefficient of variation of the times between the arrivals, `1` for Poisson arrivals.
+    pub arrival_scv: f64,
+    pub stations: Vec<Station>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TandemMetrics {
+    /// Probability that an arriving request is rejected because the first station is full.
+    pub delay_probability: f64,
+    pub mean_number_in_system: f64,
+    pub mean_sojourn_time: f64,
+    pub throughput: f64,
+    pub nodes: Vec<TandemNodeMetrics>,
+    /// Size of the solved Markov chain.
+    pub states_count: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TandemNodeMetrics {
+    /// Probability that none of the station's servers is handling a request,
+    /// the servers holding blocked requests aren't handling them.
+    pub idle_probability: f64,
+    /// Probability that at least one server holds a request the next station can't accept.
+    pub blocked_probability: f64,
+    pub mean_number_in_system: f64,
+    /// Mean fraction of the servers handling a request.
+    pub utilization: f64,
+}
+
+/// Transition of the chain as `(from, to, rate)`.
+type Transition = (usize, usize, f64);
+
+/// Number of requests at each station and how many of them are blocked on the servers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct State {
+    requests: Vec<usize>,
+    blocked: Vec<usize>,
+}
+
+impl TandemNetwork {
+    /// Largest chain [`TandemNetwork::metrics`] solves, the size of the chain is about the product
+    /// of the stations' state counts.
+    pub const MAX_STATES: usize = 50_000;
+
+    /// The network fed by renewal arrivals with the distribution of the times between them.
+    pub fn new<D: Moments>(arrival_distr: &D, stations: Vec<Station>) -> Self {
+        Self {
+            arrival_rate: 1.0 / arrival_distr.mean(),
+            arrival_scv: arrival_distr.scv(),
+            stations,
+        }
+    }
+
+    /// Solves the continuous-time Markov chain of the network for its stationary distribution,
+    /// `None` if the arrivals aren't Poisson, a station isn't exponential or has an unlimited
+    /// buffer, or the chain has more than [`TandemNetwork::MAX_STATES`] states.
+    pub fn metrics(&self) -> Option<TandemMetrics> {
+        if self.stations.is_empty()
+            || !is_exponential(self.arrival_scv)
+            || self
+                .stations
+                .iter()
+                .any(|station| !station.is_markovian() || station.capacity.is_none())
+        {
+            return None;
+        }
+
+        let (states, transitions) = self.enumerate_states()?;
+        let probabilities = stationary_distribution(states.len(), &transitions)?;
+
+        let first_capacity = self.capacity(0);
+        let delay_probability = states
+            .iter()
+            .zip(&probabilities)
+            .filter(|(state, _)| state.requests[0] == first_capacity)
+            .map(|(_, p)| p)
+            .sum::<f64>();
+
+        let nodes = (0..self.stations.len())
+            .map(|i| {
+                let servers = self.stations[i].servers;
+                let mut node = TandemNodeMetrics::default();
+
+                for (state, p) in states.iter().zip(&probabilities) {
+                    let busy = self.busy_servers(state, i);
+
+                    if busy == 0 {
+                        node.idle_probability += p;
+                    }
+                    if state.blocked[i] > 0 {
+                        node.blocked_probability += p;
+                    }
+                    node.mean_number_in_system += state.requests[i] as f64 * p;
+                    node.utilization += busy as f64 / servers as f64 * p;
+                }
+
+                node
+            })
+            .collect::<Vec<_>>();
+
+        let throughput = self.arrival_rate * (1.0 - delay_probability);
+        let mean_number_in_system = nodes
+            .iter()
+            .map(|node| node.mean_number_in_system)
+            .sum::<f64>();
+
+        Some(TandemMetrics {
+            delay_probability,
+            mean_number_in_system,
+            mean_sojourn_time: mean_number_in_system / throughput,
+            throughput,
+            nodes,
+            states_count: states.len(),
+        })
+    }
+
+    /// Finds the states reachable from the empty network and the transition rates between them,
+    /// `None` once there are more than [`TandemNetwork::MAX_STATES`] of them.
+    fn enumerate_states(&self) -> Option<(Vec<State>, Vec<Transition>)> {
+        let empty = State {
+            requests: vec![0; self.stations.len()],
+            blocked: vec![0; self.stations.len()],
+        };
+
+        let mut states = vec![empty.clone()];
+        let mut indices = HashMap::from([(empty, 0)]);
+        let mut transitions = Vec::new();
+        let mut next = 0;
+
+        while next < states.len() {
+            let state = states[next].clone();
+
+            for (target, rate) in self.transitions(&state) {
+                let target = *indices.entry(target.clone()).or_insert_with(|| {
+                    states.push(target);
+                    states.len() - 1
+                });
+                transitions.push((next, target, rate));
+            }
+
+            if states.len() > Self::MAX_STATES {
+                return None;
+            }
+            next += 1;
+        }
+
+        Some((states, transitions))
+    }
+
+    fn transitions(&self, state: &State) -> Vec<(State, f64)> {
+        let mut transitions = Vec::new();
+
+        if state.requests[0] < self.capacity(0) {
+            let mut target = state.clone();
+            target.requests[0] += 1;
+            transitions.push((target, self.arrival_rate));
+        }
+
+        for i in 0..self.stations.len() {
+            let busy = self.busy_servers(state, i);
+            if busy == 0 {
+                continue;
+            }
+
+            let rate = busy as f64 / self.stations[i].service_mean;
+            let mut target = state.clone();
+
+            if i + 1 == self.stations.len() {
+                target.requests[i] -= 1;
+                self.unblock(&mut target, i);
+            } else if target.requests[i + 1] < self.capacity(i + 1) {
+                target.requests[i] -= 1;
+                target.requests[i + 1] += 1;
+                self.unblock(&mut target, i);
+            } else {
+                target.blocked[i] += 1;
+            }
+
+            transitions.push((target, rate));
+        }
+
+        transitions
+    }
+
+    /// Moves the requests blocked before `station` on after it's got a free place,
+    /// which frees places further back.
+    fn unblock(&self, state: &mut State, station: usize) {
+        let mut station = station;
+
+        while station > 0 {
+            let previous = station - 1;
+            if state.blocked[previous] == 0 || state.requests[station] >= self.capacity(station) {
+                return;
+            }
+
+            state.blocked[previous] -= 1;
+            state.requests[previous] -= 1;
+            state.requests[station] += 1;
+            station = previous;
+        }
+    }
+
+    fn busy_servers(&self, state: &State, station: usize) -> usize {
+        let servers = self.stations[station].servers;
+        (state.requests[station] - state.blocked[station]).min(servers - state.blocked[station])
+    }
+
+    fn capacity(&self, station: usize) -> usize {
+        self.stations[station].capacity.unwrap_or_default()
+    }
+}
+
+/// Solves `πQ = 0` with `Σπ = 1`, directly for the small chains and iteratively for the big ones.
+fn stationary_distribution(states_count: usize, transitions: &[Transition]) -> Option<Vec<f64>> {
+    if states_count <= DIRECT_STATES {
+        eliminate(states_count, transitions)
+    } else {
+        gauss_seidel(states_count, transitions)
+    }
+}
+
+/// Gaussian elimination where the last balance equation is replaced by the normalization,
+/// it takes the whole generator in memory.
+fn eliminate(states_count: usize, transitions: &[Transition]) -> Option<Vec<f64>> {
+    // rows are the balance equations, i.e. the columns of the generator
+    let mut a = vec![vec![0.0; states_count]; states_count];
+    for &(from, to, rate) in transitions {
+        a[to][from] += rate;
+        a[from][from] -= rate;
+    }
+
+    let mut b = vec![0.0; states_count];
+    a[states_count - 1] = vec![1.0; states_count];
+    b[states_count - 1] = 1.0;
+
+    solve(a, b)
+}
+
+/// Gauss-Seidel iterations of the balance equations `π_j = Σ π_i q_ij / q_j` over the sparse
+/// transitions, `None` if they don't converge within [`MAX_ITERATIONS`].
+fn gauss_seidel(states_count: usize, transitions: &[Transition]) -> Option<Vec<f64>> {
+    let mut incoming = vec![Vec::new(); states_count];
+    let mut leaving_rates = vec![0.0; states_count];
+    for &(from, to, rate) in transitions {
+        incoming[to].push((from, rate));
+        leaving_rates[from] += rate;
+    }
+
+    let mut probabilities = vec![1.0 / states_count as f64; states_count];
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut change = 0.0f64;
+
+        for (state, incoming) in incoming.iter().enumerate() {
+            if leaving_rates[state] == 0.0 {
+                continue;
+            }
+
+            let probability = incoming
+                .iter()
+                .map(|&(from, rate)| probabilities[from] * rate)
+                .sum::<f64>()
+                / leaving_rates[state];
+            change = change.max((probability - probabilities[state]).abs());
+            probabilities[state] = probability;
+        }
+
+        let total = probabilities.iter().sum::<f64>();
+        probabilities
+            .iter_mut()
+            .for_each(|probability| *probability /= total);
+
+        if change < TOLERANCE {
+            return Some(probabilities);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    /// Two single-server stations without buffers and all the rates `1`. The states
+    /// `(n1, n2)` are `(0, 0)`, `(1, 0)`, `(0, 1)`, `(1, 1)` and `(1, 1)` with the first station
+    /// blocked, their balance equations give the probabilities `2/9, 3/9, 2/9, 1/9, 1/9`.
+    fn two_stations() -> TandemNetwork {
+        let station = Station {
+            servers: 1,
+            capacity: Some(1),
+            service_mean: 1.0,
+            service_variance: 1.0,
+        };
+
+        TandemNetwork {
+            arrival_rate: 1.0,
+            arrival_scv: 1.0,
+            stations: vec![station; 2],
+        }
+    }
+
+    #[test]
+    fn two_stations_with_blocking() {
+        let metrics = two_stations().metrics().unwrap();
+
+        assert_eq!(metrics.states_count, 5);
+        assert_close(metrics.delay_probability, 5.0 / 9.0);
+        assert_close(metrics.throughput, 4.0 / 9.0);
+        assert_close(metrics.mean_number_in_system, 1.0);
+        assert_close(metrics.mean_sojourn_time, 9.0 / 4.0);
+
+        let [first, second] = metrics.nodes[..] else {
+            panic!("expected two stations, got {}", metrics.nodes.len());
+        };
+        assert_close(first.idle_probability, 5.0 / 9.0);
+        assert_close(first.blocked_probability, 1.0 / 9.0);
+        assert_close(first.mean_number_in_system, 5.0 / 9.0);
+        assert_close(first.utilization, 4.0 / 9.0);
+        assert_close(second.idle_probability, 5.0 / 9.0);
+        assert_close(second.blocked_probability, 0.0);
+        assert_close(second.mean_number_in_system, 4.0 / 9.0);
+        assert_close(second.utilization, 4.0 / 9.0);
+    }
+
+    #[test]
+    fn iterations_match_elimination() {
+        let (states, transitions) = two_stations().enumerate_states().unwrap();
+        let direct = eliminate(states.len(), &transitions).unwrap();
+        let iterated = gauss_seidel(states.len(), &transitions).unwrap();
+
+        for (direct, iterated) in direct.iter().zip(&iterated) {
+            assert_close(*iterated, *direct);
+        }
+    }
+
+    #[test]
+    fn non_poisson_arrivals_are_unsolvable() {
+        let network = TandemNetwork {
+            arrival_scv: 0.0,
+            ..two_stations()
+        };
+
+        assert!(network.metrics().is_none());
+    }
+}
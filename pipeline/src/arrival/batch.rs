@@ -0,0 +1,76 @@
+use super::{Arrival, ArrivalProcess};
+use crate::{PipelineError, Time};
+use rand::{prelude::Distribution, Rng};
+
+/// Bulk arrivals: every arrival of the inner process brings a random number of requests at once.
+#[derive(Debug, Clone)]
+pub struct BatchArrivals<P, S>
+where
+    P: ArrivalProcess,
+    S: Distribution<usize>,
+{
+    arrivals: P,
+    batch_size: S,
+    /// The arrival repeated for the rest of the current batch.
+    batch: Option<(Arrival, usize)>,
+}
+
+impl<P, S> BatchArrivals<P, S>
+where
+    P: ArrivalProcess,
+    S: Distribution<usize>,
+{
+    /// `batch_size` may sample zero, then the arrival brings no requests.
+    pub fn new(arrivals: P, batch_size: S) -> Self {
+        Self {
+            arrivals,
+            batch_size,
+            batch: None,
+        }
+    }
+}
+
+impl<P, S> ArrivalProcess for BatchArrivals<P, S>
+where
+    P: ArrivalProcess,
+    S: Distribution<usize>,
+{
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival> {
+        if let Some((arrival, remaining)) = &mut self.batch {
+            let arrival = *arrival;
+            *remaining -= 1;
+
+            if *remaining == 0 {
+                self.batch = None;
+            }
+
+            return Some(arrival);
+        }
+
+        let mut time = now;
+
+        loop {
+            let arrival = self.arrivals.next_arrival(time, rng)?;
+            let size = self.batch_size.sample(rng);
+
+            if size > 1 {
+                self.batch = Some((arrival, size - 1));
+            }
+
+            if size > 0 {
+                return Some(arrival);
+            }
+
+            time = arrival.time;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.arrivals.reset();
+        self.batch = None;
+    }
+
+    fn validate(&self) -> Result<(), PipelineError> {
+        self.arrivals.validate()
+    }
+}
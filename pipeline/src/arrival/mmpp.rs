@@ -0,0 +1,200 @@
+use super::{Arrival, ArrivalProcess};
+use crate::{distr::exponential, error::non_negative, PipelineError, Time};
+use rand::{distributions::Uniform, Rng};
+
+/// A Markov-modulated Poisson process of class `0` requests: the arrival rate depends on the phase
+/// of a continuous-time Markov chain, e.g. a quiet and a bursty phase.
+#[derive(Debug, Clone)]
+pub struct MmppArrivals {
+    rates: Vec<f64>,
+    transitions: Vec<Vec<f64>>,
+    phase: usize,
+    /// Time when the current phase ends, it's sampled with the first arrival.
+    phase_end: Option<Time>,
+}
+
+impl MmppArrivals {
+    /// Creates the process starting in phase `0`, `rates[i]` is the arrival rate in phase `i`
+    /// and `transitions[i][j]` is the rate of switching from phase `i` to phase `j`.
+    pub fn new(rates: Vec<f64>, transitions: Vec<Vec<f64>>) -> Result<Self, PipelineError> {
+        if rates.is_empty() {
+            return Err(PipelineError::invalid_parameter(
+                "rates",
+                "at least one phase is required",
+            ));
+        }
+        if transitions.len() != rates.len()
+            || transitions.iter().any(|row| row.len() != rates.len())
+        {
+            return Err(PipelineError::invalid_parameter(
+                "transitions",
+                format!("must be a {0}x{0} matrix for {0} phases", rates.len()),
+            ));
+        }
+        for rate in rates.iter().chain(transitions.iter().flatten()) {
+            non_negative("rate", *rate)?;
+        }
+
+        let arrivals = Self {
+            rates,
+            transitions,
+            phase: 0,
+            phase_end: None,
+        };
+        arrivals.validate()?;
+
+        Ok(arrivals)
+    }
+
+    pub fn phase(&self) -> usize {
+        self.phase
+    }
+
+    /// Total rate of leaving the phase.
+    fn leaving_rate(&self, phase: usize) -> f64 {
+        self.transitions[phase]
+            .iter()
+            .enumerate()
+            .filter(|(next, _)| *next != phase)
+            .map(|(_, rate)| rate)
+            .sum()
+    }
+
+    /// Whether the process stops switching phases in the phase, either it has arrivals
+    /// or it's absorbing.
+    fn stops_in(&self, phase: usize) -> bool {
+        self.rates[phase] > 0.0 || self.leaving_rate(phase) == 0.0
+    }
+
+    /// Phases the chain can get to from `phase`, including `phase`.
+    fn reachable_from(&self, phase: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.rates.len()];
+        let mut stack = vec![phase];
+        reachable[phase] = true;
+
+        while let Some(phase) = stack.pop() {
+            for (next, rate) in self.transitions[phase].iter().enumerate() {
+                if *rate > 0.0 && !reachable[next] {
+                    reachable[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    fn sample_phase_end<R: Rng + ?Sized>(&self, now: Time, rng: &mut R) -> Option<Time> {
+        let leaving_rate = self.leaving_rate(self.phase);
+        (leaving_rate > 0.0)
+            .then(|| now.checked_add(exponential(1.0 / leaving_rate, rng)).ok())
+            .flatten()
+    }
+
+    fn switch_phase<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut point = rng.sample(Uniform::new(0f64, 1f64)) * self.leaving_rate(self.phase);
+
+        for (next, rate) in self.transitions[self.phase].iter().enumerate() {
+            if next == self.phase {
+                continue;
+            }
+
+            if point < *rate {
+                self.phase = next;
+                return;
+            }
+
+            point -= rate;
+        }
+    }
+}
+
+impl ArrivalProcess for MmppArrivals {
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival> {
+        if self.phase_end.is_none() {
+            self.phase_end = self.sample_phase_end(now, rng);
+        }
+
+        let mut time = now;
+
+        // Both the arrivals and the phase changes are memoryless, so the arrival is resampled
+        // from the phase change whenever the phase ends first.
+        loop {
+            let rate = self.rates[self.phase];
+            let arrival = (rate > 0.0)
+                .then(|| time.checked_add(exponential(1.0 / rate, rng)).ok())
+                .flatten();
+
+            if let Some(arrival) = arrival {
+                if self.phase_end.is_none_or(|end| arrival < end) {
+                    return Some(Arrival {
+                        time: arrival,
+                        class: 0,
+                        priority: 0,
+                        service_time: None,
+                    });
+                }
+            }
+
+            // There are no more arrivals once the process gets to an absorbing phase without them.
+            time = self.phase_end?;
+            self.switch_phase(rng);
+            self.phase_end = self.sample_phase_end(time, rng);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0;
+        self.phase_end = None;
+    }
+
+    /// Checks that every phase the process gets to leads to a phase with arrivals or to
+    /// an absorbing one, otherwise the phases switch forever while waiting for an arrival.
+    fn validate(&self) -> Result<(), PipelineError> {
+        let reachable = self.reachable_from(0);
+
+        for phase in (0..self.rates.len()).filter(|phase| reachable[*phase]) {
+            let stops = self
+                .reachable_from(phase)
+                .iter()
+                .enumerate()
+                .any(|(next, reachable)| *reachable && self.stops_in(next));
+
+            if !stops {
+                return Err(PipelineError::invalid_parameter(
+                    "rates",
+                    format!(
+                        "phase {phase} only leads to phases without arrivals that keep switching"
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_switching_without_arrivals_are_rejected() {
+        let silent = MmppArrivals::new(vec![0.0, 0.0], vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert!(silent.is_err());
+
+        // phase 0 has arrivals, but the chain gets stuck switching between phases 1 and 2
+        let stuck = MmppArrivals::new(
+            vec![1.0, 0.0, 0.0],
+            vec![
+                vec![0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0],
+                vec![0.0, 1.0, 0.0],
+            ],
+        );
+        assert!(stuck.is_err());
+
+        let absorbing = MmppArrivals::new(vec![1.0, 0.0], vec![vec![0.0, 1.0], vec![0.0, 0.0]]);
+        assert!(absorbing.is_ok());
+    }
+}
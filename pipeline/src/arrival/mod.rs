@@ -0,0 +1,139 @@
+mod batch;
+mod mmpp;
+mod nhpp;
+mod trace;
+
+pub use self::batch::BatchArrivals;
+pub use self::mmpp::MmppArrivals;
+pub use self::nhpp::{NhppArrivals, PiecewiseConstantRate, RateFn, RateFunction};
+pub use self::trace::{TraceArrivals, TraceError};
+
+use crate::{distr::Moments, PipelineError, Time};
+use rand::{prelude::Distribution, Rng};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrival {
+    pub time: Time,
+    pub class: usize,
+    pub priority: usize,
+    /// Handling time of the request known in advance, e.g. from a trace.
+    pub service_time: Option<Time>,
+}
+
+/// Generates the arrivals of requests to a pipeline.
+pub trait ArrivalProcess {
+    /// Returns the next arrival after `now`, or `None` if there are no more arrivals.
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival>;
+
+    fn reset(&mut self) {}
+
+    /// Checks that the time passes between the arrivals, otherwise infinitely many requests
+    /// arrive at once and the simulation never gets past them.
+    fn validate(&self) -> Result<(), PipelineError> {
+        Ok(())
+    }
+}
+
+/// A renewal process of class `0` requests with the interarrival times sampled from the distribution.
+impl<D> ArrivalProcess for D
+where
+    D: Distribution<Time> + Moments,
+{
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival> {
+        // an arrival past the largest time never happens
+        Some(Arrival {
+            time: now.checked_add(self.sample(rng)).ok()?,
+            class: 0,
+            priority: 0,
+            service_time: None,
+        })
+    }
+
+    fn validate(&self) -> Result<(), PipelineError> {
+        positive_mean(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct RequestClass<D>
+where
+    D: Distribution<Time>,
+{
+    pub arrival_distr: D,
+    pub priority: usize,
+}
+
+/// Merges independent arrivals of several classes, the class of a request is its index in `classes`.
+#[derive(Debug)]
+pub struct MultiClassArrivals<D>
+where
+    D: Distribution<Time>,
+{
+    classes: Vec<RequestClass<D>>,
+    next_arrivals: Vec<Option<Time>>,
+}
+
+impl<D> MultiClassArrivals<D>
+where
+    D: Distribution<Time>,
+{
+    pub fn new(classes: Vec<RequestClass<D>>) -> Self {
+        Self {
+            next_arrivals: vec![None; classes.len()],
+            classes,
+        }
+    }
+}
+
+impl<D> ArrivalProcess for MultiClassArrivals<D>
+where
+    D: Distribution<Time> + Moments,
+{
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival> {
+        for (class, next_arrival) in self.classes.iter().zip(self.next_arrivals.iter_mut()) {
+            if next_arrival.is_none() {
+                // an arrival past the largest time never happens
+                *next_arrival = now.checked_add(class.arrival_distr.sample(rng)).ok();
+            }
+        }
+
+        let (class, time) = self
+            .next_arrivals
+            .iter()
+            .enumerate()
+            .filter_map(|(class, time)| Some((class, (*time)?)))
+            .min_by(|(_, a), (_, b)| f64::from(*a).total_cmp(&f64::from(*b)))?;
+        self.next_arrivals[class] = None;
+
+        Some(Arrival {
+            time,
+            class,
+            priority: self.classes[class].priority,
+            service_time: None,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.next_arrivals.iter_mut().for_each(|time| *time = None);
+    }
+
+    fn validate(&self) -> Result<(), PipelineError> {
+        self.classes
+            .iter()
+            .try_for_each(|class| positive_mean(&class.arrival_distr))
+    }
+}
+
+/// Checks that the times between the arrivals have a positive mean.
+fn positive_mean<D: Moments + ?Sized>(distr: &D) -> Result<(), PipelineError> {
+    let mean = distr.mean();
+
+    if mean > 0.0 {
+        Ok(())
+    } else {
+        Err(PipelineError::invalid_parameter(
+            "mean",
+            format!("the times between arrivals must have a positive mean, got {mean}"),
+        ))
+    }
+}
@@ -0,0 +1,210 @@
+use super::{Arrival, ArrivalProcess};
+use crate::{distr::exponential, error::non_negative, PipelineError, Time};
+use rand::{distributions::Uniform, Rng};
+
+/// Arrival rate changing over time.
+pub trait RateFunction {
+    fn rate(&self, time: Time) -> f64;
+
+    /// Upper bound of [`RateFunction::rate`], the tighter it is the fewer candidates are thinned out.
+    fn max_rate(&self) -> f64;
+
+    /// Upper bound of the rate from `time` on, zero if no request arrives after `time`.
+    fn max_rate_after(&self, _time: Time) -> f64 {
+        self.max_rate()
+    }
+}
+
+/// Rate that is constant between breakpoints, e.g. the hourly load of a day.
+#[derive(Debug, Clone)]
+pub struct PiecewiseConstantRate {
+    /// Pieces as `(start, rate)` sorted by start, the first rate also holds before the first start.
+    pieces: Vec<(Time, f64)>,
+    /// The rate repeats itself with this period.
+    period: Option<Time>,
+}
+
+/// Rate given by a function and its upper bound till the horizon, no request arrives after it.
+/// The horizon bounds the thinning of a rate that drops to zero for good.
+#[derive(Debug, Clone, Copy)]
+pub struct RateFn<F>
+where
+    F: Fn(Time) -> f64,
+{
+    pub rate: F,
+    pub max_rate: f64,
+    pub horizon: Time,
+}
+
+/// A non-homogeneous Poisson process of class `0` requests, sampled by thinning.
+#[derive(Debug, Clone)]
+pub struct NhppArrivals<F>
+where
+    F: RateFunction,
+{
+    rate: F,
+}
+
+impl PiecewiseConstantRate {
+    pub fn new(mut pieces: Vec<(Time, f64)>) -> Result<Self, PipelineError> {
+        if pieces.is_empty() {
+            return Err(PipelineError::invalid_parameter(
+                "pieces",
+                "at least one piece is required",
+            ));
+        }
+        for (_, rate) in &pieces {
+            non_negative("rate", *rate)?;
+        }
+
+        pieces.sort_by(|(a, _), (b, _)| f64::from(*a).total_cmp(&f64::from(*b)));
+        Ok(Self {
+            pieces,
+            period: None,
+        })
+    }
+
+    /// Repeats the rate every `period`, the pieces should start within the period.
+    pub fn periodic(pieces: Vec<(Time, f64)>, period: Time) -> Result<Self, PipelineError> {
+        if period == Time::ZERO {
+            return Err(PipelineError::invalid_parameter(
+                "period",
+                "must be positive",
+            ));
+        }
+
+        Ok(Self {
+            period: Some(period),
+            ..Self::new(pieces)?
+        })
+    }
+}
+
+impl RateFunction for PiecewiseConstantRate {
+    fn rate(&self, time: Time) -> f64 {
+        let time = match self.period {
+            Some(period) => Time::saturating(f64::from(time) % f64::from(period)),
+            None => time,
+        };
+
+        self.pieces
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= time)
+            .or(self.pieces.first())
+            .map_or(0.0, |(_, rate)| *rate)
+    }
+
+    fn max_rate(&self) -> f64 {
+        self.pieces
+            .iter()
+            .map(|(_, rate)| *rate)
+            .fold(0.0, f64::max)
+    }
+
+    fn max_rate_after(&self, time: Time) -> f64 {
+        if self.period.is_some() {
+            return self.max_rate();
+        }
+
+        let current = self
+            .pieces
+            .iter()
+            .rposition(|(start, _)| *start <= time)
+            .unwrap_or(0);
+
+        self.pieces[current..]
+            .iter()
+            .map(|(_, rate)| *rate)
+            .fold(0.0, f64::max)
+    }
+}
+
+impl<F> RateFunction for RateFn<F>
+where
+    F: Fn(Time) -> f64,
+{
+    fn rate(&self, time: Time) -> f64 {
+        if time <= self.horizon {
+            (self.rate)(time)
+        } else {
+            0.0
+        }
+    }
+
+    fn max_rate(&self) -> f64 {
+        self.max_rate
+    }
+
+    fn max_rate_after(&self, time: Time) -> f64 {
+        if time < self.horizon {
+            self.max_rate
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<F> NhppArrivals<F>
+where
+    F: RateFunction,
+{
+    pub fn new(rate: F) -> Self {
+        Self { rate }
+    }
+}
+
+impl<F> ArrivalProcess for NhppArrivals<F>
+where
+    F: RateFunction,
+{
+    /// Returns `None` once the rate stays zero, e.g. after the last piece of a piecewise rate.
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival> {
+        // Candidates come at the maximum rate and each is kept with probability `rate / max_rate`.
+        // The maximum is over the rest of the time, so it only decreases as the candidates come.
+        let mut time = now;
+
+        loop {
+            let max_rate = self.rate.max_rate_after(time);
+            if max_rate <= 0.0 {
+                return None;
+            }
+
+            // a candidate past the largest time never arrives
+            time = time.checked_add(exponential(1.0 / max_rate, rng)).ok()?;
+
+            if rng.sample(Uniform::new(0f64, 1f64)) * max_rate < self.rate.rate(time) {
+                return Some(Arrival {
+                    time,
+                    class: 0,
+                    priority: 0,
+                    service_time: None,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn rate_zero_for_good_ends_at_the_horizon() {
+        let rate = RateFn {
+            rate: |time: Time| if f64::from(time) < 10.0 { 1.0 } else { 0.0 },
+            max_rate: 1.0,
+            horizon: Time::new(100.0).unwrap(),
+        };
+        let mut arrivals = NhppArrivals::new(rate);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let mut now = Time::ZERO;
+        while let Some(arrival) = arrivals.next_arrival(now, &mut rng) {
+            now = arrival.time;
+        }
+
+        assert!(f64::from(now) < 10.0);
+    }
+}
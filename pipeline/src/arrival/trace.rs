@@ -0,0 +1,183 @@
+use super::{Arrival, ArrivalProcess};
+use crate::Time;
+use rand::Rng;
+use serde::Deserialize;
+use std::io::BufRead;
+
+/// Replays the arrivals recorded in a trace, the arrivals stop at the end of the trace.
+#[derive(Debug, Clone)]
+pub struct TraceArrivals {
+    arrivals: Vec<Arrival>,
+    next: usize,
+}
+
+#[derive(Debug)]
+pub enum TraceError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+/// A trace record, only the arrival time is required.
+#[derive(Debug, Deserialize)]
+struct Record {
+    time: f64,
+    #[serde(default)]
+    class: usize,
+    #[serde(default)]
+    priority: usize,
+    service_time: Option<f64>,
+}
+
+impl TraceArrivals {
+    /// Creates the process from the arrivals at the absolute times, they are sorted by time.
+    pub fn new(mut arrivals: Vec<Arrival>) -> Self {
+        arrivals.sort_by(|a, b| f64::from(a.time).total_cmp(&f64::from(b.time)));
+        Self { arrivals, next: 0 }
+    }
+
+    /// Reads a CSV trace, the header names the columns `time`, `class`, `priority`
+    /// and `service_time`, all of them except `time` are optional.
+    pub fn read_csv<B: BufRead>(reader: B) -> Result<Self, TraceError> {
+        let mut lines = reader.lines().enumerate();
+
+        let Some((_, header)) = lines.next() else {
+            return Ok(Self::new(Vec::new()));
+        };
+        let columns = header?
+            .split(',')
+            .map(|column| column.trim().to_owned())
+            .collect::<Vec<_>>();
+
+        if !columns.iter().any(|column| column == "time") {
+            return Err(TraceError::Parse {
+                line: 1,
+                message: "missing `time` column".to_owned(),
+            });
+        }
+
+        let mut arrivals = Vec::new();
+
+        for (i, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record =
+                parse_csv_record(&columns, &line).map_err(|message| TraceError::Parse {
+                    line: i + 1,
+                    message,
+                })?;
+            arrivals.push(record.into_arrival(i + 1)?);
+        }
+
+        Ok(Self::new(arrivals))
+    }
+
+    /// Reads a trace with a JSON object per line, e.g. `{"time": 1.5, "class": 1, "service_time": 0.2}`,
+    /// the fields are the same as the columns of a CSV trace.
+    pub fn read_json_lines<B: BufRead>(reader: B) -> Result<Self, TraceError> {
+        let mut arrivals = Vec::new();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record =
+                serde_json::from_str::<Record>(&line).map_err(|err| TraceError::Parse {
+                    line: i + 1,
+                    message: err.to_string(),
+                })?;
+            arrivals.push(record.into_arrival(i + 1)?);
+        }
+
+        Ok(Self::new(arrivals))
+    }
+
+    /// Times between the consecutive arrivals, e.g. to build an empirical distribution of them.
+    pub fn interarrival_times(&self) -> Vec<f64> {
+        self.arrivals
+            .windows(2)
+            .map(|pair| f64::from(pair[1].time.saturating_sub(pair[0].time)))
+            .collect()
+    }
+}
+
+impl ArrivalProcess for TraceArrivals {
+    fn next_arrival<R: Rng + ?Sized>(&mut self, _: Time, _: &mut R) -> Option<Arrival> {
+        let arrival = self.arrivals.get(self.next).copied()?;
+        self.next += 1;
+
+        Some(arrival)
+    }
+
+    fn reset(&mut self) {
+        self.next = 0;
+    }
+}
+
+impl Record {
+    fn into_arrival(self, line: usize) -> Result<Arrival, TraceError> {
+        let time = |value: f64, field: &str| {
+            Time::new(value).map_err(|err| TraceError::Parse {
+                line,
+                message: format!("`{field}`: {err}"),
+            })
+        };
+
+        Ok(Arrival {
+            time: time(self.time, "time")?,
+            class: self.class,
+            priority: self.priority,
+            service_time: self
+                .service_time
+                .map(|service_time| time(service_time, "service_time"))
+                .transpose()?,
+        })
+    }
+}
+
+fn parse_csv_record(columns: &[String], line: &str) -> Result<Record, String> {
+    let mut record = Record {
+        time: 0.0,
+        class: 0,
+        priority: 0,
+        service_time: None,
+    };
+
+    for (column, value) in columns.iter().zip(line.split(',')) {
+        let value = value.trim();
+        let invalid = |err: &dyn std::fmt::Display| format!("invalid `{column}`: {err}");
+
+        match column.as_str() {
+            "time" => record.time = value.parse().map_err(|err| invalid(&err))?,
+            "class" => record.class = value.parse().map_err(|err| invalid(&err))?,
+            "priority" => record.priority = value.parse().map_err(|err| invalid(&err))?,
+            "service_time" if !value.is_empty() => {
+                record.service_time = Some(value.parse().map_err(|err| invalid(&err))?)
+            }
+            _ => {}
+        }
+    }
+
+    Ok(record)
+}
+
+impl From<std::io::Error> for TraceError {
+    fn from(value: std::io::Error) -> Self {
+        TraceError::Io(value)
+    }
+}
+
+impl std::fmt::Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceError::Io(err) => write!(f, "can't read trace: {err}"),
+            TraceError::Parse { line, message } => write!(f, "trace line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
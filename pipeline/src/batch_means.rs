@@ -0,0 +1,195 @@
+//! Steady-state estimation from a single long run by the method of batch means.
+
+use crate::{
+    arrival::ArrivalProcess,
+    replication::{normal_quantile, Estimate},
+    Pipeline, PipelineError, Time,
+};
+use rand::Rng;
+use serde::Serialize;
+use std::fmt::{Debug, Display};
+
+/// Splits a series of observations into batches and treats the batch means as independent,
+/// the batches are merged pairwise while their means are significantly autocorrelated.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchMeans {
+    /// Batches to start with.
+    pub batches: usize,
+    /// The batches aren't merged below this count, then the means are reported as correlated.
+    pub min_batches: usize,
+    /// Confidence level of the interval and of the autocorrelation test, e.g. `0.95`.
+    pub level: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BatchMeansReport {
+    pub batch_size: usize,
+    pub estimate: Estimate,
+    /// Lag-1 autocorrelation of the batch means.
+    pub autocorrelation: f64,
+    /// Whether the autocorrelation is insignificant, otherwise the interval is too narrow.
+    pub independent: bool,
+}
+
+impl BatchMeans {
+    /// Estimates the mean sojourn time from a run of `working_time`, the run starts from the empty
+    /// state, so a warm-up should be set on the pipeline.
+    pub fn run<ArrivalDistr, Rand>(
+        &self,
+        pipeline: &mut Pipeline<ArrivalDistr, Rand>,
+        working_time: Time,
+    ) -> Result<BatchMeansReport, PipelineError>
+    where
+        ArrivalDistr: ArrivalProcess + Debug,
+        Rand: Rng + Debug,
+    {
+        pipeline.reset();
+        let sojourn_times = pipeline
+            .work_during(working_time)?
+            .iter()
+            .map(|request| f64::from(request.handling_time()))
+            .collect::<Vec<_>>();
+
+        Ok(self.estimate(&sojourn_times))
+    }
+
+    /// Estimates the mean of the observations in the order they were made,
+    /// the observations that don't fill the last batch are left out.
+    pub fn estimate(&self, series: &[f64]) -> BatchMeansReport {
+        let mut batch_size = (series.len() / self.batches.max(1)).max(1);
+
+        loop {
+            let means = series
+                .chunks_exact(batch_size)
+                .map(|batch| batch.iter().sum::<f64>() / batch_size as f64)
+                .collect::<Vec<_>>();
+
+            let autocorrelation = lag1_autocorrelation(&means);
+            // under independence the autocorrelation is about normal with the variance `1 / k`
+            let bound = normal_quantile((1.0 + self.level) / 2.0) / (means.len() as f64).sqrt();
+            let independent = autocorrelation.abs() <= bound;
+
+            if independent || means.len() / 2 < self.min_batches.max(2) {
+                return BatchMeansReport {
+                    batch_size,
+                    estimate: Estimate::from_samples(&means, self.level),
+                    autocorrelation,
+                    independent,
+                };
+            }
+
+            batch_size *= 2;
+        }
+    }
+}
+
+impl Display for BatchMeansReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} batches of {} observations, lag-1 autocorrelation {:.4}{}",
+            self.estimate.replications,
+            self.batch_size,
+            self.autocorrelation,
+            if self.independent {
+                ""
+            } else {
+                " (significant)"
+            }
+        )?;
+        writeln!(
+            f,
+            "mean {:.5} ± {:.5}",
+            self.estimate.mean, self.estimate.half_width
+        )
+    }
+}
+
+fn lag1_autocorrelation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>();
+    if variance == 0.0 {
+        return 0.0;
+    }
+
+    let covariance = values
+        .windows(2)
+        .map(|pair| (pair[0] - mean) * (pair[1] - mean))
+        .sum::<f64>();
+
+    covariance / variance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Uniform numbers in `[0, 1)` of a linear congruential generator, the same every time.
+    fn uniform(count: usize) -> Vec<f64> {
+        let mut state = 1u64;
+
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 11) as f64 / (1u64 << 53) as f64
+            })
+            .collect()
+    }
+
+    const BATCH_MEANS: BatchMeans = BatchMeans {
+        batches: 64,
+        min_batches: 8,
+        level: 0.95,
+    };
+
+    fn assert_covers(report: &BatchMeansReport, mean: f64) {
+        let estimate = report.estimate;
+        assert!(
+            (estimate.mean - mean).abs() <= estimate.half_width,
+            "{mean} isn't within {} ± {}",
+            estimate.mean,
+            estimate.half_width
+        );
+    }
+
+    #[test]
+    fn independent_series_keeps_the_batches() {
+        let report = BATCH_MEANS.estimate(&uniform(6400));
+
+        assert!(report.independent);
+        assert_eq!(report.batch_size, 100);
+        assert_covers(&report, 0.5);
+    }
+
+    #[test]
+    fn autocorrelated_series_merges_the_batches() {
+        // AR(1) with the coefficient 0.99 around 5, the noise has the mean zero
+        let mut value = 5.0;
+        let series = uniform(64_000)
+            .into_iter()
+            .map(|u| {
+                value = 5.0 + 0.99 * (value - 5.0) + (u - 0.5);
+                value
+            })
+            .collect::<Vec<_>>();
+
+        let report = BatchMeans {
+            batches: 640,
+            ..BATCH_MEANS
+        }
+        .estimate(&series);
+
+        assert!(report.independent);
+        assert!(report.batch_size > 100);
+        assert_covers(&report, 5.0);
+    }
+}
@@ -0,0 +1,33 @@
+use super::{clamped, Moments};
+use crate::{error::non_negative, PipelineError, Time};
+use rand::prelude::Distribution;
+
+/// Always gives the same value.
+#[derive(Debug, Clone, Copy)]
+pub struct DeterministicDistr {
+    value: f64,
+}
+
+impl DeterministicDistr {
+    pub fn new(value: f64) -> Result<Self, PipelineError> {
+        Ok(Self {
+            value: non_negative("value", value)?,
+        })
+    }
+}
+
+impl Distribution<Time> for DeterministicDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, _: &mut R) -> Time {
+        clamped(self.value)
+    }
+}
+
+impl Moments for DeterministicDistr {
+    fn mean(&self) -> f64 {
+        self.value
+    }
+
+    fn variance(&self) -> f64 {
+        0.0
+    }
+}
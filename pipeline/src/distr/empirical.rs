@@ -0,0 +1,133 @@
+use super::{clamped, Moments};
+use crate::{
+    error::{non_negative, positive},
+    PipelineError, Time,
+};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+/// Resamples measured values from their histogram: a bin is chosen by its weight
+/// and the value is uniform within the bin.
+#[derive(Debug, Clone)]
+pub struct EmpiricalDistr {
+    /// Bins as `(from, to, cumulative probability)`.
+    bins: Vec<(f64, f64, f64)>,
+}
+
+impl EmpiricalDistr {
+    /// Creates the distribution from the bins given as `(from, to, weight)`, the weights are normalized.
+    pub fn from_histogram(bins: Vec<(f64, f64, f64)>) -> Result<Self, PipelineError> {
+        for (from, to, weight) in &bins {
+            non_negative("from", *from)?;
+            if !(to.is_finite() && to >= from) {
+                return Err(PipelineError::invalid_parameter(
+                    "to",
+                    format!("must be finite and not less than `from` {from}, got {to}"),
+                ));
+            }
+            non_negative("weight", *weight)?;
+        }
+
+        let total = bins.iter().map(|(_, _, weight)| weight).sum::<f64>();
+        positive("sum of the weights", total)?;
+        let mut cumulative = 0.0;
+
+        let bins = bins
+            .into_iter()
+            .map(|(from, to, weight)| {
+                cumulative += weight / total;
+                (from, to, cumulative)
+            })
+            .collect();
+
+        Ok(Self { bins })
+    }
+
+    /// Builds a histogram of the samples with `bins_count` equal-width bins.
+    pub fn from_samples(samples: &[f64], bins_count: usize) -> Result<Self, PipelineError> {
+        if samples.is_empty() {
+            return Err(PipelineError::invalid_parameter(
+                "samples",
+                "at least one sample is required",
+            ));
+        }
+        if bins_count == 0 {
+            return Err(PipelineError::invalid_parameter(
+                "bins_count",
+                "at least one bin is required",
+            ));
+        }
+        for sample in samples {
+            non_negative("samples", *sample)?;
+        }
+
+        let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = (max - min) / bins_count as f64;
+
+        if width == 0.0 {
+            return Self::from_histogram(vec![(min, max, 1.0)]);
+        }
+
+        let mut weights = vec![0.0; bins_count];
+        for sample in samples {
+            let bin = (((sample - min) / width) as usize).min(bins_count - 1);
+            weights[bin] += 1.0;
+        }
+
+        Self::from_histogram(
+            weights
+                .into_iter()
+                .enumerate()
+                .map(|(i, weight)| {
+                    let from = min + width * i as f64;
+                    (from, from + width, weight)
+                })
+                .collect(),
+        )
+    }
+
+    fn probabilities(&self) -> impl Iterator<Item = (f64, f64, f64)> + '_ {
+        let mut previous = 0.0;
+
+        self.bins.iter().map(move |&(from, to, cumulative)| {
+            let probability = cumulative - previous;
+            previous = cumulative;
+            (from, to, probability)
+        })
+    }
+}
+
+impl Distribution<Time> for EmpiricalDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let point = rng.sample(Uniform::new(0f64, 1f64));
+        let (from, to, _) = self
+            .bins
+            .iter()
+            .find(|(_, _, cumulative)| point < *cumulative)
+            .or(self.bins.last())
+            .copied()
+            .unwrap_or_default();
+
+        let rand_value = rng.sample(Uniform::new(0f64, 1f64));
+        clamped(from + (to - from) * rand_value)
+    }
+}
+
+impl Moments for EmpiricalDistr {
+    fn mean(&self) -> f64 {
+        self.probabilities()
+            .map(|(from, to, probability)| probability * (from + to) / 2.0)
+            .sum()
+    }
+
+    fn variance(&self) -> f64 {
+        let second_moment = self
+            .probabilities()
+            .map(|(from, to, probability)| {
+                probability * (from.powi(2) + from * to + to.powi(2)) / 3.0
+            })
+            .sum::<f64>();
+
+        second_moment - self.mean().powi(2)
+    }
+}
@@ -0,0 +1,47 @@
+use super::{clamped, Moments};
+use crate::{error::positive, PipelineError, Time};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+/// Sum of `k` exponential phases with the total mean `mean`.
+#[derive(Debug, Clone, Copy)]
+pub struct ErlangDistr {
+    k: usize,
+    mean: f64,
+}
+
+impl ErlangDistr {
+    pub fn new(k: usize, mean: f64) -> Result<Self, PipelineError> {
+        if k == 0 {
+            return Err(PipelineError::invalid_parameter(
+                "k",
+                "at least one phase is required",
+            ));
+        }
+
+        Ok(Self {
+            k,
+            mean: positive("mean", mean)?,
+        })
+    }
+}
+
+impl Distribution<Time> for ErlangDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let phase_mean = self.mean / self.k as f64;
+        let converted_value = (0..self.k)
+            .map(|_| -(1f64 - rng.sample(Uniform::new(0f64, 1f64))).ln() * phase_mean)
+            .sum::<f64>();
+
+        clamped(converted_value)
+    }
+}
+
+impl Moments for ErlangDistr {
+    fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    fn variance(&self) -> f64 {
+        self.mean.powi(2) / self.k as f64
+    }
+}
@@ -0,0 +1,38 @@
+use super::{clamped, Moments};
+use crate::{error::positive, PipelineError, Time};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExpDistr {
+    mean: f64,
+}
+
+impl ExpDistr {
+    pub fn new(mean: f64) -> Result<Self, PipelineError> {
+        Ok(Self {
+            mean: positive("mean", mean)?,
+        })
+    }
+}
+
+impl Distribution<Time> for ExpDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        exponential(self.mean, rng)
+    }
+}
+
+/// Samples the exponential distribution with a mean checked by the caller.
+pub(crate) fn exponential<R: Rng + ?Sized>(mean: f64, rng: &mut R) -> Time {
+    let rand_value = rng.sample(Uniform::new(0f64, 1f64));
+    clamped(-(1f64 - rand_value).ln() * mean)
+}
+
+impl Moments for ExpDistr {
+    fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    fn variance(&self) -> f64 {
+        self.mean.powi(2)
+    }
+}
@@ -0,0 +1,58 @@
+use super::{clamped, special::standard_normal, Moments};
+use crate::{error::positive, PipelineError, Time};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+#[derive(Debug, Clone, Copy)]
+pub struct GammaDistr {
+    shape: f64,
+    scale: f64,
+}
+
+impl GammaDistr {
+    pub fn new(shape: f64, scale: f64) -> Result<Self, PipelineError> {
+        Ok(Self {
+            shape: positive("shape", shape)?,
+            scale: positive("scale", scale)?,
+        })
+    }
+}
+
+impl Distribution<Time> for GammaDistr {
+    // Marsaglia and Tsang method, the shapes less than one are boosted by one.
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let (shape, boost) = if self.shape < 1.0 {
+            let rand_value = 1f64 - rng.sample(Uniform::new(0f64, 1f64));
+            (self.shape + 1.0, rand_value.powf(1.0 / self.shape))
+        } else {
+            (self.shape, 1.0)
+        };
+
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+
+        let value = loop {
+            let x = standard_normal(rng);
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+
+            let rand_value = rng.sample(Uniform::new(0f64, 1f64));
+            if rand_value.ln() < x * x / 2.0 + d - d * v + d * v.ln() {
+                break d * v;
+            }
+        };
+
+        clamped(value * boost * self.scale)
+    }
+}
+
+impl Moments for GammaDistr {
+    fn mean(&self) -> f64 {
+        self.shape * self.scale
+    }
+
+    fn variance(&self) -> f64 {
+        self.shape * self.scale.powi(2)
+    }
+}
@@ -0,0 +1,71 @@
+use super::{exponential, Moments};
+use crate::{
+    error::{non_negative, positive},
+    PipelineError, Time,
+};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+/// Mixture of exponential phases, each phase is given as `(probability, mean)`.
+#[derive(Debug, Clone)]
+pub struct HyperExpDistr {
+    phases: Vec<(f64, f64)>,
+}
+
+impl HyperExpDistr {
+    /// The probabilities are normalized by their sum.
+    pub fn new(phases: Vec<(f64, f64)>) -> Result<Self, PipelineError> {
+        for (probability, mean) in &phases {
+            non_negative("probability", *probability)?;
+            positive("mean", *mean)?;
+        }
+
+        let total = phases
+            .iter()
+            .map(|(probability, _)| probability)
+            .sum::<f64>();
+        positive("sum of the probabilities", total)?;
+
+        Ok(Self {
+            phases: phases
+                .into_iter()
+                .map(|(probability, mean)| (probability / total, mean))
+                .collect(),
+        })
+    }
+}
+
+impl Distribution<Time> for HyperExpDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let mut point = rng.sample(Uniform::new(0f64, 1f64));
+        let mean = self
+            .phases
+            .iter()
+            .find(|(probability, _)| {
+                point -= probability;
+                point < 0.0
+            })
+            .or(self.phases.last())
+            .map_or(0.0, |(_, mean)| *mean);
+
+        exponential(mean, rng)
+    }
+}
+
+impl Moments for HyperExpDistr {
+    fn mean(&self) -> f64 {
+        self.phases
+            .iter()
+            .map(|(probability, mean)| probability * mean)
+            .sum()
+    }
+
+    fn variance(&self) -> f64 {
+        let second_moment = self
+            .phases
+            .iter()
+            .map(|(probability, mean)| 2.0 * probability * mean.powi(2))
+            .sum::<f64>();
+
+        second_moment - self.mean().powi(2)
+    }
+}
@@ -0,0 +1,46 @@
+use super::{clamped, special::standard_normal, Moments};
+use crate::{
+    error::{finite, non_negative},
+    PipelineError, Time,
+};
+use rand::prelude::Distribution;
+
+/// The logarithm of the value is normally distributed with the mean `mu` and the deviation `sigma`.
+#[derive(Debug, Clone, Copy)]
+pub struct LogNormalDistr {
+    mu: f64,
+    sigma: f64,
+}
+
+impl LogNormalDistr {
+    /// Fails if the mean `exp(mu + sigma^2 / 2)` overflows, the samples would overflow too often.
+    pub fn new(mu: f64, sigma: f64) -> Result<Self, PipelineError> {
+        let (mu, sigma) = (finite("mu", mu)?, non_negative("sigma", sigma)?);
+        let distr = Self { mu, sigma };
+
+        if !distr.mean().is_finite() {
+            return Err(PipelineError::invalid_parameter(
+                "mu",
+                format!("the mean exp(mu + sigma^2 / 2) overflows for mu {mu} and sigma {sigma}"),
+            ));
+        }
+
+        Ok(distr)
+    }
+}
+
+impl Distribution<Time> for LogNormalDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        clamped((self.mu + self.sigma * standard_normal(rng)).exp())
+    }
+}
+
+impl Moments for LogNormalDistr {
+    fn mean(&self) -> f64 {
+        (self.mu + self.sigma.powi(2) / 2.0).exp()
+    }
+
+    fn variance(&self) -> f64 {
+        (self.sigma.powi(2).exp() - 1.0) * (2.0 * self.mu + self.sigma.powi(2)).exp()
+    }
+}
@@ -0,0 +1,70 @@
+mod deterministic;
+mod empirical;
+mod erlang;
+mod exp;
+mod gamma;
+mod hyper_exp;
+mod log_normal;
+mod pareto;
+pub(crate) mod special;
+mod trunc_normal;
+mod uniform;
+mod weibull;
+
+pub use self::deterministic::DeterministicDistr;
+pub use self::empirical::EmpiricalDistr;
+pub use self::erlang::ErlangDistr;
+pub use self::exp::ExpDistr;
+pub use self::gamma::GammaDistr;
+pub use self::hyper_exp::HyperExpDistr;
+pub use self::log_normal::LogNormalDistr;
+pub use self::pareto::ParetoDistr;
+pub use self::trunc_normal::TruncNormalDistr;
+pub use self::uniform::UniformDistr;
+pub use self::weibull::WeibullDistr;
+
+pub(crate) use self::exp::exponential;
+
+use crate::{PipelineError, Time};
+
+/// Analytical moments of a distribution, to compare the simulation results with the theory.
+pub trait Moments {
+    fn mean(&self) -> f64;
+
+    fn variance(&self) -> f64;
+
+    /// Squared coefficient of variation.
+    fn scv(&self) -> f64 {
+        self.variance() / self.mean().powi(2)
+    }
+}
+
+/// Smallest `1 - U` for a uniform `U` in `[0, 1)`, it gives the largest value of the
+/// distributions sampled by inverting their CDF.
+const MIN_COMPLEMENT: f64 = f64::EPSILON / 2.0;
+
+/// Converts a sampled value to time, the negative values and NaN become zero and the values
+/// that overflow become the largest finite one.
+fn clamped(value: f64) -> Time {
+    if value.is_nan() {
+        return Time::ZERO;
+    }
+
+    Time::saturating(value.clamp(0.0, f64::MAX))
+}
+
+/// Checks that the largest value the distribution samples is finite.
+fn sampled_finite(
+    parameter: &'static str,
+    value: f64,
+    largest_sample: f64,
+) -> Result<f64, PipelineError> {
+    if largest_sample.is_finite() {
+        Ok(value)
+    } else {
+        Err(PipelineError::invalid_parameter(
+            parameter,
+            format!("{value} makes the samples overflow"),
+        ))
+    }
+}
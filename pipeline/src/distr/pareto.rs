@@ -0,0 +1,47 @@
+use super::{clamped, sampled_finite, Moments, MIN_COMPLEMENT};
+use crate::{error::positive, PipelineError, Time};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+/// Pareto distribution with the minimal value `scale`, the mean is infinite
+/// for `shape <= 1` and the variance is infinite for `shape <= 2`.
+#[derive(Debug, Clone, Copy)]
+pub struct ParetoDistr {
+    shape: f64,
+    scale: f64,
+}
+
+impl ParetoDistr {
+    pub fn new(shape: f64, scale: f64) -> Result<Self, PipelineError> {
+        let (shape, scale) = (positive("shape", shape)?, positive("scale", scale)?);
+
+        Ok(Self {
+            shape: sampled_finite("shape", shape, scale / MIN_COMPLEMENT.powf(1.0 / shape))?,
+            scale,
+        })
+    }
+}
+
+impl Distribution<Time> for ParetoDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let rand_value = 1f64 - rng.sample(Uniform::new(0f64, 1f64));
+        clamped(self.scale / rand_value.powf(1.0 / self.shape))
+    }
+}
+
+impl Moments for ParetoDistr {
+    fn mean(&self) -> f64 {
+        if self.shape <= 1.0 {
+            return f64::INFINITY;
+        }
+
+        self.shape * self.scale / (self.shape - 1.0)
+    }
+
+    fn variance(&self) -> f64 {
+        if self.shape <= 2.0 {
+            return f64::INFINITY;
+        }
+
+        self.scale.powi(2) * self.shape / ((self.shape - 1.0).powi(2) * (self.shape - 2.0))
+    }
+}
@@ -0,0 +1,89 @@
+use rand::{distributions::Uniform, Rng};
+use std::f64::consts::PI;
+
+/// Samples the standard normal distribution with the Box-Muller transform.
+pub(super) fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let u1 = 1f64 - rng.sample(Uniform::new(0f64, 1f64));
+    let u2 = rng.sample(Uniform::new(0f64, 1f64));
+
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+/// Parameter `g` of the Lanczos approximation and its coefficients for `n = 9`.
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// Series of the Lanczos approximation in `x = z - 1`.
+fn lanczos_series(x: f64) -> f64 {
+    LANCZOS_COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        })
+}
+
+/// Gamma function, computed with the Lanczos approximation.
+pub(super) fn gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+
+    let x = x - 1.0;
+    let t = x + LANCZOS_G + 0.5;
+
+    (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * lanczos_series(x)
+}
+
+/// Logarithm of the gamma function for positive `x`, computed with the Lanczos approximation,
+/// it doesn't overflow where `gamma` does.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    let x = x - 1.0;
+    let t = x + LANCZOS_G + 0.5;
+
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + lanczos_series(x).ln()
+}
+
+/// Density of the standard normal distribution.
+pub(super) fn normal_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+/// Ratio of the density to the tail probability of the standard normal distribution in `x`,
+/// the tail is a continued fraction past 3 where `1 - normal_cdf(x)` loses its precision.
+pub(super) fn normal_hazard(x: f64) -> f64 {
+    if x < 3.0 {
+        return normal_pdf(x) / (1.0 - normal_cdf(x));
+    }
+
+    // 1 / hazard = 1 / (x + 1 / (x + 2 / (x + 3 / (x + ...))))
+    (1..=40)
+        .rev()
+        .fold(x, |fraction, k| x + k as f64 / fraction)
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+pub(super) fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / 2f64.sqrt()))
+}
+
+/// Error function, computed with the Abramowitz and Stegun approximation 7.1.26.
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let polynomial = t
+        * (0.254_829_6
+            + t * (-0.284_496_74 + t * (1.421_413_8 + t * (-1.453_152_1 + t * 1.061_405_4))));
+    let value = 1.0 - polynomial * (-x * x).exp();
+
+    value.copysign(x)
+}
@@ -0,0 +1,75 @@
+use super::{
+    clamped, exponential,
+    special::{normal_hazard, standard_normal},
+    Moments,
+};
+use crate::{
+    error::{finite, positive},
+    PipelineError, Time,
+};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
+
+/// Normal distribution truncated to the non-negative values, `mean` and `deviation`
+/// are the parameters of the normal distribution before the truncation.
+#[derive(Debug, Clone, Copy)]
+pub struct TruncNormalDistr {
+    mean: f64,
+    deviation: f64,
+}
+
+impl TruncNormalDistr {
+    pub fn new(mean: f64, deviation: f64) -> Result<Self, PipelineError> {
+        Ok(Self {
+            mean: finite("mean", mean)?,
+            deviation: positive("deviation", deviation)?,
+        })
+    }
+
+    /// Standardized truncation point and the ratio of the density to the tail probability in it.
+    fn truncation(&self) -> (f64, f64) {
+        let alpha = -self.mean / self.deviation;
+        (alpha, normal_hazard(alpha))
+    }
+}
+
+impl Distribution<Time> for TruncNormalDistr {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let (alpha, _) = self.truncation();
+        clamped(self.mean + self.deviation * standard_normal_above(alpha, rng))
+    }
+}
+
+/// Samples the standard normal distribution truncated to the values above `alpha`.
+/// Rejecting the normal samples below it takes at most two tries on average while `alpha`
+/// is negative, further in the tail the proposals are exponential, shifted to `alpha`
+/// (Robert, 1995), so the number of tries stays bounded however far the tail is.
+fn standard_normal_above<R: Rng + ?Sized>(alpha: f64, rng: &mut R) -> f64 {
+    if alpha <= 0.0 {
+        loop {
+            let value = standard_normal(rng);
+            if value >= alpha {
+                return value;
+            }
+        }
+    }
+
+    let rate = (alpha + (alpha * alpha + 4.0).sqrt()) / 2.0;
+    loop {
+        let value = alpha + f64::from(exponential(1.0 / rate, rng));
+        if rng.sample(Uniform::new(0f64, 1f64)) <= (-(value - rate).powi(2) / 2.0).exp() {
+            return value;
+        }
+    }
+}
+
+impl Moments for TruncNormalDistr {
+    fn mean(&self) -> f64 {
+        let (_, ratio) = self.truncation();
+        self.mean + self.deviation * ratio
+    }
+
+    fn variance(&self) -> f64 {
+        let (alpha, ratio) = self.truncation();
+        self.deviation.powi(2) * (1.0 + alpha * ratio - ratio.powi(2))
+    }
+}
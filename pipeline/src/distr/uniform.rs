@@ -0,0 +1,40 @@
+use super::{clamped, Moments};
+use crate::{error::non_negative, PipelineError, Time};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+#[derive(Debug, Clone, Copy)]
+pub struct UniformDistr {
+    min: f64,
+    max: f64,
+}
+
+impl UniformDistr {
+    pub fn new(min: f64, max: f64) -> Result<Self, PipelineError> {
+        let min = non_negative("min", min)?;
+        if !(max.is_finite() && max >= min) {
+            return Err(PipelineError::invalid_parameter(
+                "max",
+                format!("must be finite and not less than `min` {min}, got {max}"),
+            ));
+        }
+
+        Ok(Self { min, max })
+    }
+}
+
+impl Distribution<Time> for UniformDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let rand_value = rng.sample(Uniform::new(0f64, 1f64));
+        clamped(self.min + (self.max - self.min) * rand_value)
+    }
+}
+
+impl Moments for UniformDistr {
+    fn mean(&self) -> f64 {
+        (self.min + self.max) / 2.0
+    }
+
+    fn variance(&self) -> f64 {
+        (self.max - self.min).powi(2) / 12.0
+    }
+}
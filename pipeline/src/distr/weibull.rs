@@ -0,0 +1,39 @@
+use super::{clamped, sampled_finite, special::gamma, Moments, MIN_COMPLEMENT};
+use crate::{error::positive, PipelineError, Time};
+use rand::{distributions::Uniform, prelude::Distribution};
+
+#[derive(Debug, Clone, Copy)]
+pub struct WeibullDistr {
+    shape: f64,
+    scale: f64,
+}
+
+impl WeibullDistr {
+    pub fn new(shape: f64, scale: f64) -> Result<Self, PipelineError> {
+        let (shape, scale) = (positive("shape", shape)?, positive("scale", scale)?);
+        let largest_sample = scale * (-MIN_COMPLEMENT.ln()).powf(1.0 / shape);
+
+        Ok(Self {
+            shape: sampled_finite("shape", shape, largest_sample)?,
+            scale,
+        })
+    }
+}
+
+impl Distribution<Time> for WeibullDistr {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        let rand_value = rng.sample(Uniform::new(0f64, 1f64));
+        let converted_value = self.scale * (-(1f64 - rand_value).ln()).powf(1.0 / self.shape);
+        clamped(converted_value)
+    }
+}
+
+impl Moments for WeibullDistr {
+    fn mean(&self) -> f64 {
+        self.scale * gamma(1.0 + 1.0 / self.shape)
+    }
+
+    fn variance(&self) -> f64 {
+        self.scale.powi(2) * gamma(1.0 + 2.0 / self.shape) - self.mean().powi(2)
+    }
+}
@@ -0,0 +1,79 @@
+use crate::TimeError;
+use std::fmt;
+
+/// Error of building a pipeline from invalid parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineError {
+    /// A parameter of a distribution, an arrival process or a node is out of its domain.
+    InvalidParameter {
+        parameter: &'static str,
+        message: String,
+    },
+    /// The network links lead nowhere or a node lacks its links.
+    InvalidNetwork(String),
+    InvalidTime(TimeError),
+}
+
+impl PipelineError {
+    pub fn invalid_parameter(parameter: &'static str, message: impl Into<String>) -> Self {
+        Self::InvalidParameter {
+            parameter,
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks that the parameter is a finite number greater than zero.
+pub(crate) fn positive(parameter: &'static str, value: f64) -> Result<f64, PipelineError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(PipelineError::invalid_parameter(
+            parameter,
+            format!("must be positive, got {value}"),
+        ))
+    }
+}
+
+/// Checks that the parameter is a finite number not less than zero.
+pub(crate) fn non_negative(parameter: &'static str, value: f64) -> Result<f64, PipelineError> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(PipelineError::invalid_parameter(
+            parameter,
+            format!("must be non-negative, got {value}"),
+        ))
+    }
+}
+
+pub(crate) fn finite(parameter: &'static str, value: f64) -> Result<f64, PipelineError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(PipelineError::invalid_parameter(
+            parameter,
+            format!("must be finite, got {value}"),
+        ))
+    }
+}
+
+impl From<TimeError> for PipelineError {
+    fn from(value: TimeError) -> Self {
+        Self::InvalidTime(value)
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::InvalidParameter { parameter, message } => {
+                write!(f, "invalid `{parameter}`: {message}")
+            }
+            PipelineError::InvalidNetwork(message) => write!(f, "invalid network: {message}"),
+            PipelineError::InvalidTime(err) => write!(f, "invalid time: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
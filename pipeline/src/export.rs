@@ -0,0 +1,185 @@
+//! Export of the handled requests for analysis in other tools, a row per request and station passed,
+//! and of the estimated distribution functions, e.g. [`Statistics::handling_time_cdf`](crate::Statistics::handling_time_cdf).
+
+use crate::Request;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Debug, Serialize)]
+struct TraceRow {
+    request_id: usize,
+    class: usize,
+    priority: usize,
+    arrival_time: f64,
+    leaving_time: f64,
+    node: usize,
+    entered_at: f64,
+    started_at: Option<f64>,
+    finished_at: Option<f64>,
+    left_at: Option<f64>,
+    waiting_time: Option<f64>,
+    service_time: Option<f64>,
+    blocking_time: Option<f64>,
+}
+
+const COLUMNS: &str = "request_id,class,priority,arrival_time,leaving_time,node,entered_at,\
+started_at,finished_at,left_at,waiting_time,service_time,blocking_time";
+
+/// Writes the visits as CSV with a header row, the unknown times are left empty.
+pub fn write_csv<W: Write>(requests: &[Request], mut writer: W) -> std::io::Result<()> {
+    writeln!(writer, "{COLUMNS}")?;
+
+    for row in rows(requests) {
+        let optional = |value: Option<f64>| value.map_or(String::new(), |value| value.to_string());
+
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            row.request_id,
+            row.class,
+            row.priority,
+            row.arrival_time,
+            row.leaving_time,
+            row.node,
+            row.entered_at,
+            optional(row.started_at),
+            optional(row.finished_at),
+            optional(row.left_at),
+            optional(row.waiting_time),
+            optional(row.service_time),
+            optional(row.blocking_time),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes the visits as JSON objects with the same fields as the CSV columns, one per line.
+pub fn write_json_lines<W: Write>(requests: &[Request], mut writer: W) -> std::io::Result<()> {
+    for row in rows(requests) {
+        serde_json::to_writer(&mut writer, &row)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the points `(time, probability)` of a distribution function as CSV with a header row.
+pub fn write_cdf_csv<W: Write>(cdf: &[(f64, f64)], mut writer: W) -> std::io::Result<()> {
+    writeln!(writer, "time,probability")?;
+
+    for (time, probability) in cdf {
+        writeln!(writer, "{time},{probability}")?;
+    }
+
+    Ok(())
+}
+
+fn rows(requests: &[Request]) -> impl Iterator<Item = TraceRow> + '_ {
+    requests.iter().flat_map(|request| {
+        request.visits.iter().map(move |visit| TraceRow {
+            request_id: request.id,
+            class: request.class,
+            priority: request.priority,
+            arrival_time: f64::from(request.arrival_time),
+            leaving_time: f64::from(request.leaving_time),
+            node: visit.node.index(),
+            entered_at: f64::from(visit.entered_at),
+            started_at: visit.started_at.map(f64::from),
+            finished_at: visit.finished_at.map(f64::from),
+            left_at: visit.left_at.map(f64::from),
+            waiting_time: visit.waiting_time().map(f64::from),
+            service_time: visit.service_time().map(f64::from),
+            blocking_time: visit.blocking_time().map(f64::from),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{network::NodeId, request::Visit, Time};
+
+    fn time(value: f64) -> Time {
+        Time::new(value).unwrap()
+    }
+
+    /// A request that has passed station 0 and has been blocked at station 2, which dropped it
+    /// before starting to handle it. The times have no short decimal form.
+    fn request() -> Request {
+        Request {
+            id: 7,
+            class: 1,
+            priority: 2,
+            arrival_time: time(0.1),
+            leaving_time: time(0.1 + 0.2),
+            service_time: None,
+            visits: vec![
+                Visit {
+                    node: NodeId(0),
+                    entered_at: time(0.1),
+                    started_at: Some(time(0.15)),
+                    finished_at: Some(time(0.2)),
+                    left_at: Some(time(1.0 / 3.0)),
+                },
+                Visit {
+                    node: NodeId(2),
+                    entered_at: time(1.0 / 3.0),
+                    started_at: None,
+                    finished_at: None,
+                    left_at: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let mut csv = Vec::new();
+        write_csv(&[request()], &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(COLUMNS));
+
+        // every field is a number or empty, so none of them is quoted
+        let rows = lines
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        assert!(rows
+            .iter()
+            .all(|row| row.len() == COLUMNS.split(',').count()));
+
+        let number =
+            |field: &str| -> Option<f64> { (!field.is_empty()).then(|| field.parse().unwrap()) };
+        let [first, second] = [&rows[0], &rows[1]];
+        assert_eq!(&first[..3], ["7", "1", "2"]);
+        assert_eq!(number(first[4]), Some(0.1 + 0.2));
+        assert_eq!(number(first[9]), Some(1.0 / 3.0));
+        assert_eq!(number(first[10]), Some(0.15 - 0.1));
+        assert_eq!(number(first[12]), Some(1.0 / 3.0 - 0.2));
+        assert_eq!(second[5], "2");
+        assert!(second[7..].iter().all(|field| field.is_empty()));
+    }
+
+    #[test]
+    fn json_lines_have_the_csv_columns() {
+        let mut json = Vec::new();
+        write_json_lines(&[request()], &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        let rows = json
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        for row in &rows {
+            let keys = row.as_object().unwrap().keys().collect::<Vec<_>>();
+            assert_eq!(keys.len(), COLUMNS.split(',').count());
+            assert!(COLUMNS.split(',').all(|column| row.get(column).is_some()));
+        }
+        assert_eq!(rows[0]["left_at"], 1.0 / 3.0);
+        assert!(rows[1]["started_at"].is_null());
+    }
+}
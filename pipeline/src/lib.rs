@@ -1,129 +1,660 @@
+pub mod analytic;
+pub mod arrival;
+pub mod batch_means;
+pub mod distr;
+mod error;
+pub mod export;
+pub mod network;
 pub mod node;
+pub mod quantile;
+pub mod replication;
 mod request;
 mod requests_accum;
+mod scheduler;
 mod statistics;
+pub mod streams;
 mod time;
+pub mod warm_up;
 
-use rand::{prelude::Distribution, Rng};
+use rand::{Rng, SeedableRng};
 use std::fmt::Debug;
 
-pub use crate::request::Request;
-pub use crate::statistics::Statistics;
-pub use crate::time::Time;
+use crate::arrival::{Arrival, ArrivalProcess};
+use crate::network::{BlockingPolicy, Network, NodeId, Target, Vertex};
+use crate::node::{BlockedError, NodeContext, PipelineNodeStatistics};
+use crate::quantile::QuantileSketch;
+use crate::scheduler::{EventKind, Scheduler};
+use crate::statistics::ratio;
+use crate::streams::{stream, stream_seed, Antithetic, Stream};
+
+pub use crate::error::PipelineError;
+pub use crate::request::{Request, Visit};
+pub use crate::statistics::{ClassStatistics, Statistics};
+pub use crate::time::{Time, TimeError};
 
 #[derive(Debug)]
-pub struct PipelineParams<ArrivalDistr, Rand>
-where
-    Rand: Rng,
-{
+pub struct PipelineParams<ArrivalDistr> {
     pub arrival_distr: ArrivalDistr,
-    pub rand_gen: Rand,
+    /// Seed of the random streams of the arrivals, the routing and every node,
+    /// a run is reproduced from it bit for bit.
+    pub seed: u64,
 }
 
 #[derive(Debug)]
 pub struct Pipeline<ArrivalDistr, Rand>
 where
-    ArrivalDistr: Distribution<Time>,
+    ArrivalDistr: ArrivalProcess,
     Rand: Rng,
 {
     arrival_distr: ArrivalDistr,
     requests_accum: requests_accum::RequestsAccum,
     delayed_requests_count: usize,
     requests_count: usize,
+    classes: Vec<ClassCounters>,
+    /// Times from arriving to leaving the pipeline of the handled requests.
+    handling_times: QuantileSketch,
+    last_request_id: usize,
     working_time: Time,
-    start_node: Box<dyn node::PipelineNode>,
-    rng: Rand,
+    warm_up: Time,
+    /// Start of the period the statistics are gathered for, it's the end of the warm-up once it's over.
+    statistics_since: Time,
+    scheduler: Scheduler,
+    entry: Target,
+    vertices: Vec<Vertex>,
+    blocked: Vec<BlockedRequest>,
+    /// Whether the blocked requests are being offered again.
+    retrying: bool,
+    seed: u64,
+    arrival_rng: Antithetic<Rand>,
+    routing_rng: Antithetic<Rand>,
 }
 
-impl<ArrivalDistr, Rand> PipelineParams<ArrivalDistr, Rand>
+#[derive(Debug, Default, Clone, Copy)]
+struct ClassCounters {
+    requests_count: usize,
+    delayed_requests_count: usize,
+    /// Accepted requests dropped by a station.
+    lost_requests_count: usize,
+}
+
+/// A request that has been handled by the station `node` but isn't accepted by the target yet.
+/// The target is past the routers, so the request waits for the route it was sampled.
+#[derive(Debug, Clone)]
+struct BlockedRequest {
+    node: usize,
+    tag: usize,
+    target: Target,
+    request: Request,
+}
+
+impl<ArrivalDistr> PipelineParams<ArrivalDistr>
 where
-    ArrivalDistr: Distribution<Time>,
-    Rand: Rng + Clone,
+    ArrivalDistr: ArrivalProcess,
 {
-    pub fn build(
+    /// Builds a pipeline where requests pass the nodes one by one.
+    pub fn build<Rand: Rng + SeedableRng>(
         self,
         nodes: Vec<Box<dyn node::IntoPipelineNode<Rand>>>,
-    ) -> Pipeline<ArrivalDistr, Rand> {
-        let (requests_accum, mut start_node): (_, Box<dyn node::PipelineNode>) =
-            requests_accum::requests_accum();
+    ) -> Result<Pipeline<ArrivalDistr, Rand>, PipelineError> {
+        self.build_network(Network::chain(nodes))
+    }
 
-        for node in nodes.into_iter().rev() {
-            start_node = node.into_node(self.rand_gen.clone(), start_node);
-        }
+    /// Builds a pipeline of the network, fails if the arrivals or a node's parameters are invalid
+    /// or a link leads to a node missing from the network.
+    pub fn build_network<Rand: Rng + SeedableRng>(
+        self,
+        network: Network<Rand>,
+    ) -> Result<Pipeline<ArrivalDistr, Rand>, PipelineError> {
+        self.arrival_distr.validate()?;
+        let (entry, vertices) = network.into_vertices(self.seed)?;
 
-        Pipeline {
-            start_node,
+        let mut pipeline = Pipeline {
+            entry,
+            vertices,
             arrival_distr: self.arrival_distr,
-            rng: self.rand_gen,
-            requests_accum,
+            seed: self.seed,
+            arrival_rng: Antithetic::new(stream(self.seed, Stream::Arrivals), false),
+            routing_rng: Antithetic::new(stream(self.seed, Stream::Routing), false),
+            requests_accum: Default::default(),
+            scheduler: Default::default(),
+            blocked: Vec::new(),
+            retrying: false,
             working_time: Time::ZERO,
+            warm_up: Time::ZERO,
+            statistics_since: Time::ZERO,
+            last_request_id: 0,
             delayed_requests_count: 0,
             requests_count: 0,
-        }
+            classes: Vec::new(),
+            handling_times: QuantileSketch::default(),
+        };
+
+        pipeline.schedule_arrival();
+        Ok(pipeline)
     }
 }
 
 impl<ArrivalDistr, Rand> Pipeline<ArrivalDistr, Rand>
 where
-    ArrivalDistr: Distribution<Time> + Debug,
+    ArrivalDistr: ArrivalProcess + Debug,
     Rand: Rng + Debug,
 {
     pub fn reset(&mut self) {
         self.requests_accum.clear();
-        self.start_node.reset();
+        self.scheduler.clear();
+        self.blocked.clear();
+        self.vertices.iter_mut().for_each(|vertex| vertex.reset());
         self.delayed_requests_count = 0;
         self.requests_count = 0;
+        self.classes.clear();
+        self.handling_times.clear();
+        self.last_request_id = 0;
         self.working_time = Time::ZERO;
+        self.statistics_since = Time::ZERO;
+        self.arrival_distr.reset();
+        self.schedule_arrival();
+        self.schedule_warm_up_end();
+    }
+
+    /// Leaves the requests arrived and the nodes' idle time before `warm_up` out of the statistics,
+    /// so they aren't biased by the start from the empty state. The warm-up is a part of the working time.
+    pub fn set_warm_up(&mut self, warm_up: Time) {
+        self.warm_up = warm_up;
+        self.schedule_warm_up_end();
+    }
+
+    pub fn warm_up(&self) -> Time {
+        self.warm_up
+    }
+
+    /// Runs the simulation for `working_time` more, fails if the time gets past the largest
+    /// finite one, e.g. a sampled handling time is so long that its end can't be represented.
+    pub fn work_during(&mut self, working_time: Time) -> Result<Vec<Request>, PipelineError> {
+        self.working_time = self.working_time.checked_add(working_time)?;
+
+        while let Some(event) = self.scheduler.pop_until(self.working_time) {
+            match event.kind {
+                EventKind::Arrival {
+                    class,
+                    priority,
+                    service_time,
+                } => self.handle_arrival(class, priority, service_time),
+                EventKind::Completion { node, tag } => self.handle_completion(node, tag),
+                EventKind::Unblock { node, tag } => self.handle_unblock(node, tag),
+                EventKind::WarmUpEnd => self.handle_warm_up_end(),
+            }
+
+            if let Some(err) = self.scheduler.take_error() {
+                return Err(err.into());
+            }
+        }
+        self.scheduler.advance_to(self.working_time);
+
+        Ok(self.requests_accum.to_vec())
+    }
+
+    pub fn get_statistics(&self) -> Statistics {
+        let working_time = self.observed_time();
+
+        Statistics {
+            nodes: self
+                .vertices
+                .iter()
+                .filter_map(|vertex| match vertex {
+                    Vertex::Station {
+                        node,
+                        lost_requests_count,
+                        blocked_arrivals_count,
+                        ..
+                    } => Some(PipelineNodeStatistics {
+                        lost_requests_count: *lost_requests_count as f64,
+                        blocked_arrivals_count: *blocked_arrivals_count as f64,
+                        ..node.get_statistics(self.scheduler.now())
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            delayed_requests_count: self.delayed_requests_count as f64,
+            handled_requests_number: (self.requests_count - self.delayed_requests_count) as f64,
+            completed_requests_number: self.requests_accum.iter().count() as f64,
+            requests_number: self.requests_count as f64,
+            working_time,
+            probability_of_request_delay: ratio(
+                self.delayed_requests_count as f64,
+                self.requests_count as f64,
+            ),
+            average_handling_time: ratio(
+                self.requests_accum
+                    .iter()
+                    .map(|req| f64::from(req.handling_time()))
+                    .sum::<f64>(),
+                self.requests_accum.iter().count() as f64,
+            ),
+            handling_time_quantiles: self.handling_times.quantiles(),
+            handling_time_cdf: self.handling_times.cdf(),
+            classes: self
+                .classes
+                .iter()
+                .enumerate()
+                .map(|(class, counters)| self.get_class_statistics(class, counters))
+                .collect(),
+        }
+    }
+
+    fn get_class_statistics(&self, class: usize, counters: &ClassCounters) -> ClassStatistics {
+        let requests = self.requests_accum.iter().filter(|req| req.class == class);
+        let handling_times = requests
+            .clone()
+            .map(|req| f64::from(req.handling_time()))
+            .collect::<Vec<_>>();
+        let waiting_time = requests
+            .flat_map(|req| &req.visits)
+            .filter_map(Visit::waiting_time)
+            .map(f64::from)
+            .sum::<f64>();
+
+        let lost_requests_count = counters.delayed_requests_count + counters.lost_requests_count;
+
+        ClassStatistics {
+            requests_number: counters.requests_count as f64,
+            handled_requests_number: counters.requests_count.saturating_sub(lost_requests_count)
+                as f64,
+            completed_requests_number: handling_times.len() as f64,
+            delayed_requests_count: counters.delayed_requests_count as f64,
+            lost_requests_count: counters.lost_requests_count as f64,
+            probability_of_request_delay: ratio(
+                lost_requests_count as f64,
+                counters.requests_count as f64,
+            ),
+            average_handling_time: ratio(
+                handling_times.iter().sum::<f64>(),
+                handling_times.len() as f64,
+            ),
+            throughput: ratio(handling_times.len() as f64, f64::from(self.observed_time())),
+            average_waiting_time: ratio(waiting_time, handling_times.len() as f64),
+        }
+    }
+
+    /// Working time without the warm-up.
+    fn observed_time(&self) -> Time {
+        self.working_time.saturating_sub(self.statistics_since)
     }
 
-    pub fn work_during<T: Into<Time>>(&mut self, working_time: T) -> Vec<Request> {
-        let working_time = working_time.into();
-        self.working_time += working_time;
-        let mut counter = Time::ZERO;
+    fn handle_arrival(&mut self, class: usize, priority: usize, service_time: Option<Time>) {
+        let now = self.scheduler.now();
+        self.last_request_id += 1;
+        let request = Request {
+            id: self.last_request_id,
+            class,
+            priority,
+            arrival_time: now,
+            leaving_time: now,
+            service_time,
+            visits: Vec::new(),
+        };
+
+        if self.classes.len() <= class {
+            self.classes.resize(class + 1, ClassCounters::default());
+        }
+
+        if self.deliver(self.entry, &request).is_err() {
+            self.delayed_requests_count += 1;
+            self.classes[class].delayed_requests_count += 1;
+        }
+
+        self.requests_count += 1;
+        self.classes[class].requests_count += 1;
+        self.schedule_arrival();
+    }
+
+    fn handle_warm_up_end(&mut self) {
+        // the end of a warm-up that has been changed since it was scheduled
+        if self.scheduler.now() < self.warm_up || self.statistics_since >= self.warm_up {
+            return;
+        }
+
+        let now = self.scheduler.now();
+        self.statistics_since = now;
+        self.requests_accum.clear();
+        self.delayed_requests_count = 0;
+        self.requests_count = 0;
+        self.classes.clear();
+        self.handling_times.clear();
+        self.vertices
+            .iter_mut()
+            .for_each(|vertex| vertex.reset_statistics(now));
+    }
+
+    fn handle_completion(&mut self, node: usize, tag: usize) {
+        let can_start = self.can_start(node);
+        let Vertex::Station {
+            node: station,
+            next,
+            blocking_policy,
+            ..
+        } = &mut self.vertices[node]
+        else {
+            return;
+        };
+
+        let (next, blocking_policy) = (*next, *blocking_policy);
+        let mut ctx = NodeContext::new(node, can_start, &mut self.scheduler);
+        let Some(mut request) = station.complete(&mut ctx, tag) else {
+            return;
+        };
+        request.leave_station(self.scheduler.now());
+        let next = self.choose_target(next);
+
+        if self.deliver(next, &request).is_ok() {
+            return self.handle_unblock(node, tag);
+        }
+
+        let target = match blocking_policy {
+            BlockingPolicy::Bas | BlockingPolicy::Bbs => next,
+            BlockingPolicy::Drop => {
+                if let Vertex::Station {
+                    lost_requests_count,
+                    ..
+                } = &mut self.vertices[node]
+                {
+                    *lost_requests_count += 1;
+                }
+                // the requests arrived during the warm-up are left out
+                if request.arrival_time >= self.statistics_since {
+                    if let Some(counters) = self.classes.get_mut(request.class) {
+                        counters.lost_requests_count += 1;
+                    }
+                }
+
+                return self.handle_unblock(node, tag);
+            }
+            BlockingPolicy::Reroute(overflow) => {
+                let overflow = self.choose_target(overflow);
+                if self.deliver(overflow, &request).is_ok() {
+                    return self.handle_unblock(node, tag);
+                }
+
+                overflow
+            }
+        };
+
+        self.blocked.push(BlockedRequest {
+            node,
+            tag,
+            target,
+            request,
+        });
+    }
+
+    fn handle_unblock(&mut self, node: usize, tag: usize) {
+        let can_start = self.can_start(node);
+        if let Vertex::Station { node: station, .. } = &mut self.vertices[node] {
+            station.unblock(
+                &mut NodeContext::new(node, can_start, &mut self.scheduler),
+                tag,
+            );
+        }
 
         loop {
-            let arrival_time = self.arrival_distr.sample(&mut self.rng);
+            self.resume_stations();
 
-            if counter + arrival_time > working_time {
+            if !self.retry_blocked() {
                 break;
             }
+        }
+    }
+
+    /// Hands blocked requests over to the nodes that have free space now, the nodes that
+    /// were holding them are unblocked by separate events at the current time.
+    /// Returns whether any request is handed over.
+    fn retry_blocked(&mut self) -> bool {
+        let mut i = 0;
+        let mut retried = false;
+        self.retrying = true;
 
-            counter += arrival_time;
+        while i < self.blocked.len() {
+            let now = self.scheduler.now();
+            let blocked = &mut self.blocked[i];
+            blocked.request.leave_station(now);
+            let (node, tag, target, request) = (
+                blocked.node,
+                blocked.tag,
+                blocked.target,
+                blocked.request.clone(),
+            );
 
-            let request = Request {
-                arrival_time: counter,
-                leaving_time: counter,
+            if self.deliver(target, &request).is_ok() {
+                self.blocked.remove(i);
+                self.scheduler
+                    .schedule(self.scheduler.now(), EventKind::Unblock { node, tag });
+                retried = true;
+            } else {
+                i += 1;
+            }
+        }
+
+        self.retrying = false;
+        retried
+    }
+
+    /// Lets the stations blocking before service start handling requests if the next nodes have free space.
+    fn resume_stations(&mut self) {
+        for id in 0..self.vertices.len() {
+            let Vertex::Station {
+                blocking_policy: BlockingPolicy::Bbs,
+                ..
+            } = self.vertices[id]
+            else {
+                continue;
             };
 
-            if let Err(_) = self
-                .start_node
-                .push_request(&mut arrival_time.clone(), request)
-            {
-                self.delayed_requests_count += 1;
+            if !self.can_start(id) {
+                continue;
             }
 
-            self.requests_count += 1;
+            if let Vertex::Station { node, .. } = &mut self.vertices[id] {
+                node.resume(&mut NodeContext::new(id, true, &mut self.scheduler));
+            }
         }
+    }
 
-        self.requests_accum.to_vec()
+    /// Whether the station may start handling a request according to its blocking policy.
+    fn can_start(&self, id: usize) -> bool {
+        match self.vertices[id] {
+            Vertex::Station {
+                blocking_policy: BlockingPolicy::Bbs,
+                next,
+                ..
+            } => self.can_deliver(next, None),
+            _ => true,
+        }
     }
 
-    pub fn get_statistics(&self) -> Statistics {
-        Statistics {
-            nodes: self.start_node.get_statistics(),
-            delayed_requests_count: self.delayed_requests_count as f32,
-            handled_requests_number: (self.requests_count - self.delayed_requests_count) as f32,
-            requests_number: self.requests_count as f32,
-            working_time: self.working_time,
-            probability_of_request_delay: self.delayed_requests_count as f32
-                / self.requests_count as f32,
-            average_handling_time: self
-                .requests_accum
+    /// Samples the routes of the routers from the target on, so the result is the node
+    /// the request is offered to. The network has no cycles of routers, so it terminates.
+    fn choose_target(&mut self, mut target: Target) -> Target {
+        while let Target::Node(NodeId(id)) = target {
+            let Vertex::Router { routes } = &self.vertices[id] else {
+                break;
+            };
+            target = choose_route(routes, &mut self.routing_rng);
+        }
+
+        target
+    }
+
+    /// Pushes the request to the target, the request is either accepted as a whole
+    /// or not accepted by any node, e.g. a fork doesn't pass copies unless every branch accepts them.
+    fn deliver(&mut self, target: Target, request: &Request) -> Result<(), BlockedError> {
+        let Target::Node(NodeId(id)) = target else {
+            let mut request = request.clone();
+            request.leaving_time = self.scheduler.now();
+            // the requests arrived during the warm-up are left out
+            if request.arrival_time >= self.statistics_since {
+                self.handling_times.push(f64::from(request.handling_time()));
+                self.requests_accum.push(request);
+            }
+            return Ok(());
+        };
+
+        let can_start = self.can_start(id);
+
+        match &mut self.vertices[id] {
+            Vertex::Station {
+                node,
+                blocked_arrivals_count,
+                ..
+            } => {
+                let mut request = request.clone();
+                request.enter_station(NodeId(id), self.scheduler.now());
+
+                let result = node.push_request(
+                    &mut NodeContext::new(id, can_start, &mut self.scheduler),
+                    request,
+                );
+
+                // a blocked request is offered again and again, it's counted once
+                if result.is_err() && !self.retrying {
+                    *blocked_arrivals_count += 1;
+                }
+
+                result
+            }
+            Vertex::Router { routes } => {
+                let route = choose_route(routes, &mut self.routing_rng);
+                self.deliver(route, request)
+            }
+            Vertex::Fork { branches } => {
+                let branches = branches.clone();
+                if !branches.iter().all(|b| self.can_deliver(*b, Some(request))) {
+                    return Err(BlockedError);
+                }
+
+                branches
+                    .into_iter()
+                    .try_for_each(|branch| self.deliver(branch, request))
+            }
+            Vertex::Join {
+                parts,
+                arrived,
+                next,
+            } => {
+                let arrived_parts = arrived.entry(request.id).or_insert(0);
+                if *arrived_parts + 1 < *parts {
+                    *arrived_parts += 1;
+                    return Ok(());
+                }
+
+                let next = *next;
+                if !self.can_deliver(next, Some(request)) {
+                    return Err(BlockedError);
+                }
+
+                if let Vertex::Join { arrived, .. } = &mut self.vertices[id] {
+                    arrived.remove(&request.id);
+                }
+                self.deliver(next, request)
+            }
+        }
+    }
+
+    /// Whether the target would accept the request, without a request it's whether the target
+    /// would accept any request.
+    fn can_deliver(&self, target: Target, request: Option<&Request>) -> bool {
+        let Target::Node(NodeId(id)) = target else {
+            return true;
+        };
+
+        match &self.vertices[id] {
+            Vertex::Station { node, .. } => node.can_accept(),
+            Vertex::Router { routes } => routes
+                .iter()
+                .all(|(route, _)| self.can_deliver(*route, request)),
+            Vertex::Fork { branches } => branches
                 .iter()
-                .map(|req| req.leaving_time - req.arrival_time)
-                .map(|t| f32::from(t))
-                .sum::<f32>()
-                / self.requests_accum.iter().count() as f32,
+                .all(|branch| self.can_deliver(*branch, request)),
+            vertex @ Vertex::Join { next, .. } => {
+                request.is_some_and(|request| !vertex.completes_join(request))
+                    || self.can_deliver(*next, request)
+            }
+        }
+    }
+}
+
+impl<ArrivalDistr, Rand> Pipeline<ArrivalDistr, Rand>
+where
+    ArrivalDistr: ArrivalProcess,
+    Rand: Rng + SeedableRng,
+{
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the random streams of the arrivals, the routing and every node from `seed`,
+    /// it should be followed by [`Pipeline::reset`] since the next arrival is already sampled.
+    /// The `antithetic` streams give `1 - U` for every uniform `U` of the original ones.
+    ///
+    /// [`Pipeline::reset`] alone goes on with the streams, so the runs after it differ.
+    pub fn reseed(&mut self, seed: u64, antithetic: bool) {
+        self.seed = seed;
+        self.arrival_rng = Antithetic::new(stream(seed, Stream::Arrivals), antithetic);
+        self.routing_rng = Antithetic::new(stream(seed, Stream::Routing), antithetic);
+        self.vertices
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, vertex)| vertex.reseed(stream_seed(seed, Stream::Node(i)), antithetic));
+    }
+}
+
+impl<ArrivalDistr, Rand> Pipeline<ArrivalDistr, Rand>
+where
+    ArrivalDistr: ArrivalProcess,
+    Rand: Rng,
+{
+    fn schedule_warm_up_end(&mut self) {
+        if self.warm_up > self.scheduler.now() {
+            self.scheduler.schedule(self.warm_up, EventKind::WarmUpEnd);
+        }
+    }
+
+    /// Schedules the next arrival, nothing is scheduled once the arrival process is exhausted.
+    fn schedule_arrival(&mut self) {
+        let Some(Arrival {
+            time,
+            class,
+            priority,
+            service_time,
+        }) = self
+            .arrival_distr
+            .next_arrival(self.scheduler.now(), &mut self.arrival_rng)
+        else {
+            return;
+        };
+
+        self.scheduler.schedule(
+            time,
+            EventKind::Arrival {
+                class,
+                priority,
+                service_time,
+            },
+        );
+    }
+}
+
+fn choose_route<R: Rng>(routes: &[(Target, f64)], rng: &mut R) -> Target {
+    let total = routes
+        .iter()
+        .map(|(_, probability)| probability)
+        .sum::<f64>();
+    let mut point = rng.gen_range(0.0..total);
+
+    for &(route, probability) in routes {
+        if point < probability {
+            return route;
         }
+        point -= probability;
     }
+
+    routes.last().map_or(Target::Exit, |(route, _)| *route)
 }
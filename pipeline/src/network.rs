@@ -0,0 +1,554 @@
+use crate::node::{IntoPipelineNode, PipelineNode};
+use crate::request::Request;
+use crate::streams::{stream, Stream};
+use crate::{error::non_negative, PipelineError, Time};
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(pub(crate) usize);
+
+/// Where a request goes after leaving a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Node(NodeId),
+    Exit,
+}
+
+impl NodeId {
+    /// Position of the node in the order of adding it to the network.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl From<NodeId> for Target {
+    fn from(value: NodeId) -> Self {
+        Target::Node(value)
+    }
+}
+
+/// What a station does with a handled request when the next node is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BlockingPolicy {
+    /// Blocking after service, the station holds the request until the next node accepts it.
+    #[default]
+    Bas,
+    /// Blocking before service, the station doesn't start handling a request while the next node is full.
+    /// The space isn't reserved, so the station falls back to BAS if the next node fills up meanwhile.
+    Bbs,
+    /// The request is lost.
+    Drop,
+    /// The request goes to the overflow node instead, and the station holds it until that node accepts it.
+    Reroute(Target),
+}
+
+/// Describes a queueing network, the nodes are referred by the ids returned on adding them,
+/// so the links can be added in any order, including the ones leading back to earlier nodes.
+pub struct Network<R>
+where
+    R: Rng,
+{
+    elements: Vec<Element<R>>,
+    entry: Option<Target>,
+}
+
+enum Element<R>
+where
+    R: Rng,
+{
+    Station {
+        node: Box<dyn IntoPipelineNode<R>>,
+        next: Target,
+        blocking_policy: BlockingPolicy,
+    },
+    Router {
+        routes: Vec<(Target, f64)>,
+    },
+    Fork {
+        branches: Vec<Target>,
+    },
+    Join {
+        parts: usize,
+        next: Target,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum VisitState {
+    New,
+    Visiting,
+    Done,
+}
+
+/// A built network element, stations are the only ones that take time to pass.
+#[derive(Debug)]
+pub(crate) enum Vertex {
+    Station {
+        node: Box<dyn PipelineNode>,
+        next: Target,
+        blocking_policy: BlockingPolicy,
+        lost_requests_count: usize,
+        blocked_arrivals_count: usize,
+    },
+    /// Sends a request to one of the routes, chosen with the given probabilities.
+    Router { routes: Vec<(Target, f64)> },
+    /// Sends a copy of a request to every branch.
+    Fork { branches: Vec<Target> },
+    /// Passes a request on once `parts` copies with its id have arrived.
+    Join {
+        parts: usize,
+        next: Target,
+        arrived: HashMap<usize, usize>,
+    },
+}
+
+impl<R> Network<R>
+where
+    R: Rng,
+{
+    pub fn new() -> Self {
+        Self {
+            elements: Vec::new(),
+            entry: None,
+        }
+    }
+
+    /// Creates a network where requests pass the nodes one by one.
+    pub fn chain(nodes: Vec<Box<dyn IntoPipelineNode<R>>>) -> Self {
+        let count = nodes.len();
+        let elements = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| Element::Station {
+                node,
+                next: if i + 1 < count {
+                    Target::Node(NodeId(i + 1))
+                } else {
+                    Target::Exit
+                },
+                blocking_policy: BlockingPolicy::default(),
+            })
+            .collect();
+
+        Self {
+            elements,
+            entry: None,
+        }
+    }
+
+    pub fn add_node(&mut self, node: Box<dyn IntoPipelineNode<R>>) -> NodeId {
+        self.add(Element::Station {
+            node,
+            next: Target::Exit,
+            blocking_policy: BlockingPolicy::default(),
+        })
+    }
+
+    pub fn add_router(&mut self) -> NodeId {
+        self.add(Element::Router { routes: Vec::new() })
+    }
+
+    pub fn add_fork(&mut self) -> NodeId {
+        self.add(Element::Fork {
+            branches: Vec::new(),
+        })
+    }
+
+    pub fn add_join(&mut self, parts: usize) -> NodeId {
+        self.add(Element::Join {
+            parts,
+            next: Target::Exit,
+        })
+    }
+
+    /// Sets the node the arriving requests are pushed to, it's the first added node by default.
+    pub fn set_entry<T: Into<Target>>(&mut self, target: T) {
+        self.entry = Some(target.into());
+    }
+
+    /// Sets the next node of a station or a join, or adds a branch to a fork.
+    pub fn connect<T: Into<Target>>(&mut self, from: NodeId, to: T) -> Result<(), PipelineError> {
+        match self.element_mut(from)? {
+            Element::Station { next, .. } | Element::Join { next, .. } => *next = to.into(),
+            Element::Fork { branches } => branches.push(to.into()),
+            Element::Router { .. } => {
+                return Err(PipelineError::InvalidNetwork(format!(
+                    "router {} routes need a probability",
+                    from.0
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_blocking_policy(
+        &mut self,
+        station: NodeId,
+        policy: BlockingPolicy,
+    ) -> Result<(), PipelineError> {
+        match self.element_mut(station)? {
+            Element::Station {
+                blocking_policy, ..
+            } => *blocking_policy = policy,
+            _ => {
+                return Err(PipelineError::InvalidNetwork(format!(
+                    "node {} isn't a station, only stations have a blocking policy",
+                    station.0
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a route to a router, the probabilities of all routes are normalized by their sum.
+    pub fn route<T: Into<Target>>(
+        &mut self,
+        router: NodeId,
+        to: T,
+        probability: f64,
+    ) -> Result<(), PipelineError> {
+        let probability = non_negative("probability", probability)?;
+
+        match self.element_mut(router)? {
+            Element::Router { routes } => routes.push((to.into(), probability)),
+            _ => {
+                return Err(PipelineError::InvalidNetwork(format!(
+                    "node {} isn't a router, only routers have probabilistic routes",
+                    router.0
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds the nodes, each with its own stream derived from `seed`, fails if a link leads
+    /// outside the network or a node can't pass requests on.
+    pub(crate) fn into_vertices(self, seed: u64) -> Result<(Target, Vec<Vertex>), PipelineError>
+    where
+        R: SeedableRng,
+    {
+        self.validate()?;
+
+        let entry = self.entry.unwrap_or(if self.elements.is_empty() {
+            Target::Exit
+        } else {
+            Target::Node(NodeId(0))
+        });
+
+        let vertices = self
+            .elements
+            .into_iter()
+            .enumerate()
+            .map(|(i, element)| {
+                Ok(match element {
+                    Element::Station {
+                        node,
+                        next,
+                        blocking_policy,
+                    } => Vertex::Station {
+                        node: node.into_node(stream(seed, Stream::Node(i)))?,
+                        next,
+                        blocking_policy,
+                        lost_requests_count: 0,
+                        blocked_arrivals_count: 0,
+                    },
+                    Element::Router { routes } => Vertex::Router { routes },
+                    Element::Fork { branches } => Vertex::Fork { branches },
+                    Element::Join { parts, next } => Vertex::Join {
+                        parts,
+                        next,
+                        arrived: HashMap::new(),
+                    },
+                })
+            })
+            .collect::<Result<_, PipelineError>>()?;
+
+        Ok((entry, vertices))
+    }
+
+    fn validate(&self) -> Result<(), PipelineError> {
+        let check_target = |from: &str, target: Target| match target {
+            Target::Node(NodeId(id)) if id >= self.elements.len() => {
+                Err(PipelineError::InvalidNetwork(format!(
+                    "{from} leads to node {id}, but the network has {} nodes",
+                    self.elements.len()
+                )))
+            }
+            _ => Ok(()),
+        };
+
+        if let Some(entry) = self.entry {
+            check_target("the entry", entry)?;
+        }
+
+        for (i, element) in self.elements.iter().enumerate() {
+            let from = format!("node {i}");
+
+            match element {
+                Element::Station {
+                    next,
+                    blocking_policy,
+                    ..
+                } => {
+                    check_target(&from, *next)?;
+                    if let BlockingPolicy::Reroute(overflow) = blocking_policy {
+                        check_target(&from, *overflow)?;
+                    }
+                }
+                Element::Router { routes } => {
+                    if routes.iter().all(|(_, probability)| *probability == 0.0) {
+                        return Err(PipelineError::InvalidNetwork(format!(
+                            "router {i} has no route with a positive probability"
+                        )));
+                    }
+                    for (target, _) in routes {
+                        check_target(&from, *target)?;
+                    }
+                }
+                Element::Fork { branches } => {
+                    if branches.is_empty() {
+                        return Err(PipelineError::InvalidNetwork(format!(
+                            "fork {i} has no branches"
+                        )));
+                    }
+                    for target in branches {
+                        check_target(&from, *target)?;
+                    }
+                }
+                Element::Join { parts, next } => {
+                    if *parts == 0 {
+                        return Err(PipelineError::InvalidNetwork(format!(
+                            "join {i} waits for no parts"
+                        )));
+                    }
+                    check_target(&from, *next)?;
+                }
+            }
+        }
+
+        self.check_instant_cycles()?;
+        self.check_joins()?;
+        self.check_fork_branches()
+    }
+
+    /// Routers, forks and joins pass a request on at once, so a cycle of them would pass it
+    /// around forever.
+    fn check_instant_cycles(&self) -> Result<(), PipelineError> {
+        fn visit<R: Rng>(
+            network: &Network<R>,
+            i: usize,
+            states: &mut [VisitState],
+        ) -> Result<(), PipelineError> {
+            match states[i] {
+                VisitState::Done => return Ok(()),
+                VisitState::Visiting => {
+                    return Err(PipelineError::InvalidNetwork(format!(
+                        "node {i} is in a cycle of routers, forks and joins, requests would never leave it"
+                    )))
+                }
+                VisitState::New => {}
+            }
+
+            states[i] = VisitState::Visiting;
+            for target in network.elements[i].targets() {
+                if let Target::Node(NodeId(next)) = target {
+                    if !matches!(network.elements[next], Element::Station { .. }) {
+                        visit(network, next, states)?;
+                    }
+                }
+            }
+            states[i] = VisitState::Done;
+
+            Ok(())
+        }
+
+        let mut states = vec![VisitState::New; self.elements.len()];
+        for (i, element) in self.elements.iter().enumerate() {
+            if !matches!(element, Element::Station { .. }) {
+                visit(self, i, &mut states)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every fork needs a join its branches lead to that waits for as many parts as there
+    /// are branches, otherwise the copies are never joined back.
+    fn check_joins(&self) -> Result<(), PipelineError> {
+        for (i, element) in self.elements.iter().enumerate() {
+            let Element::Fork { branches } = element else {
+                continue;
+            };
+
+            let joined = (0..self.elements.len()).any(|join| {
+                matches!(self.elements[join], Element::Join { parts, .. } if parts == branches.len())
+                    && branches
+                        .iter()
+                        .all(|branch| self.reachable(*branch).contains(&join))
+            });
+            if !joined {
+                return Err(PipelineError::InvalidNetwork(format!(
+                    "fork {i} has no join of its {} branches",
+                    branches.len()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The parts of a forked request must all get to the join, so the stations in the branches
+    /// can't drop or reroute them and the branches can't lead to the exit.
+    fn check_fork_branches(&self) -> Result<(), PipelineError> {
+        for (i, element) in self.elements.iter().enumerate() {
+            let Element::Fork { branches } = element else {
+                continue;
+            };
+
+            let mut visited = HashSet::new();
+            let mut stack = branches.clone();
+            while let Some(target) = stack.pop() {
+                let Target::Node(NodeId(id)) = target else {
+                    return Err(PipelineError::InvalidNetwork(format!(
+                        "a branch of fork {i} leads to the exit before the join"
+                    )));
+                };
+                if !visited.insert(id) {
+                    continue;
+                }
+
+                match &self.elements[id] {
+                    Element::Station {
+                        blocking_policy: BlockingPolicy::Drop | BlockingPolicy::Reroute(_),
+                        ..
+                    } => {
+                        return Err(PipelineError::InvalidNetwork(format!(
+                            "node {id} in a branch of fork {i} drops or reroutes requests, \
+                             so their parts would never be joined"
+                        )));
+                    }
+                    Element::Join { .. } => {}
+                    element => stack.extend(element.targets()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Nodes a request pushed to the target may visit.
+    fn reachable(&self, target: Target) -> HashSet<usize> {
+        let mut visited = HashSet::new();
+        let mut stack = vec![target];
+
+        while let Some(target) = stack.pop() {
+            if let Target::Node(NodeId(i)) = target {
+                if visited.insert(i) {
+                    stack.extend(self.elements[i].targets());
+                }
+            }
+        }
+
+        visited
+    }
+
+    fn element_mut(&mut self, id: NodeId) -> Result<&mut Element<R>, PipelineError> {
+        let count = self.elements.len();
+
+        self.elements.get_mut(id.0).ok_or_else(|| {
+            PipelineError::InvalidNetwork(format!(
+                "node {} is missing, the network has {count} nodes",
+                id.0
+            ))
+        })
+    }
+
+    fn add(&mut self, element: Element<R>) -> NodeId {
+        self.elements.push(element);
+        NodeId(self.elements.len() - 1)
+    }
+}
+
+impl<R> Element<R>
+where
+    R: Rng,
+{
+    /// Nodes the element may pass a request to.
+    fn targets(&self) -> Vec<Target> {
+        match self {
+            Element::Station {
+                next,
+                blocking_policy,
+                ..
+            } => match blocking_policy {
+                BlockingPolicy::Reroute(overflow) => vec![*next, *overflow],
+                _ => vec![*next],
+            },
+            Element::Router { routes } => routes.iter().map(|(target, _)| *target).collect(),
+            Element::Fork { branches } => branches.clone(),
+            Element::Join { next, .. } => vec![*next],
+        }
+    }
+}
+
+impl<R> Default for Network<R>
+where
+    R: Rng,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vertex {
+    pub fn reset(&mut self) {
+        match self {
+            Vertex::Station {
+                node,
+                lost_requests_count,
+                blocked_arrivals_count,
+                ..
+            } => {
+                node.reset();
+                *lost_requests_count = 0;
+                *blocked_arrivals_count = 0;
+            }
+            Vertex::Join { arrived, .. } => arrived.clear(),
+            Vertex::Router { .. } | Vertex::Fork { .. } => {}
+        }
+    }
+
+    pub fn reset_statistics(&mut self, now: Time) {
+        if let Vertex::Station {
+            node,
+            lost_requests_count,
+            blocked_arrivals_count,
+            ..
+        } = self
+        {
+            node.reset_statistics(now);
+            *lost_requests_count = 0;
+            *blocked_arrivals_count = 0;
+        }
+    }
+
+    pub fn reseed(&mut self, seed: u64, antithetic: bool) {
+        if let Vertex::Station { node, .. } = self {
+            node.reseed(seed, antithetic);
+        }
+    }
+
+    /// Whether a join completes with the request, i.e. the request passes the join.
+    pub fn completes_join(&self, request: &Request) -> bool {
+        match self {
+            Vertex::Join { parts, arrived, .. } => {
+                arrived.get(&request.id).copied().unwrap_or(0) + 1 >= *parts
+            }
+            _ => false,
+        }
+    }
+}
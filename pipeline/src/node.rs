@@ -1,29 +1,134 @@
-use crate::{request::Request, Time};
+use crate::{
+    quantile::Quantiles,
+    request::Request,
+    scheduler::{EventKind, Scheduler},
+    PipelineError, Time,
+};
 use rand::Rng;
+use serde::Serialize;
 use std::fmt::Debug;
 
-#[derive(Debug, Default, Clone, Copy)]
+/// Statistics of a node, the averages are weighted by time, so `average_requests_number`
+/// is about `throughput * average_sojourn_time` by Little's law. The ratios are `None`
+/// while they're undefined, e.g. before any time has passed.
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct PipelineNodeStatistics {
-    pub idle_time: f32,
+    /// Time during which none of the node's servers were handling a request.
+    pub idle_time: f64,
+    /// Time during which some server was holding a request the next node couldn't accept.
+    pub blocked_time: f64,
+    /// Name of the order the node handles its buffered requests in.
+    pub discipline: &'static str,
+    pub preemptions_count: f64,
+    /// Requests dropped because the next node was full.
+    pub lost_requests_count: f64,
+    /// Requests the node couldn't accept because it was full.
+    pub blocked_arrivals_count: f64,
+    /// Number of requests in the node, including the ones being handled and the blocked ones.
+    pub average_requests_number: Option<f64>,
+    pub average_queue_length: Option<f64>,
+    pub max_queue_length: f64,
+    /// Fraction of time the node's servers were handling requests.
+    pub utilization: Option<f64>,
+    /// Requests that have left the node.
+    pub handled_requests_count: f64,
+    pub throughput: Option<f64>,
+    /// Time from entering the node until leaving it.
+    pub average_sojourn_time: Option<f64>,
+    /// Estimated quantiles of the time from entering the node until starting to be handled.
+    pub waiting_time_quantiles: Option<Quantiles>,
+    /// Points `(time, probability)` of the estimated distribution function of the waiting time,
+    /// it isn't averaged over runs.
+    pub waiting_time_cdf: Vec<(f64, f64)>,
+    /// Fraction of time the node had each number of requests in it.
+    pub occupancy: Vec<f64>,
+    pub servers: Vec<ServerStatistics>,
 }
 
-pub trait PipelineNode: Debug {
-    fn push_request(&mut self, delta_time: &mut Time, request: Request) -> Result<(), BlockedError>;
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ServerStatistics {
+    pub busy_time: f64,
+    /// Time during which the server was holding a handled request the next node couldn't accept.
+    pub blocked_time: f64,
+    /// Time during which the server was neither handling nor holding a request.
+    pub idle_time: f64,
+}
+
+pub trait PipelineNode: Debug + Send {
+    /// Offers a request to the node at the current simulation time.
+    fn push_request(&mut self, ctx: &mut NodeContext, request: Request)
+        -> Result<(), BlockedError>;
+
+    /// Handles a completion scheduled with [`NodeContext::schedule_completion`].
+    ///
+    /// The returned request leaves the node, which keeps holding it until [`PipelineNode::unblock`]
+    /// is called with the same `tag`, i.e. until the next node accepts the request.
+    fn complete(&mut self, ctx: &mut NodeContext, tag: usize) -> Option<Request>;
+
+    fn unblock(&mut self, ctx: &mut NodeContext, tag: usize);
+
+    /// Lets the node start handling the requests it has postponed while [`NodeContext::can_start`] was false.
+    fn resume(&mut self, ctx: &mut NodeContext);
+
+    /// Whether [`PipelineNode::push_request`] would accept a request right now.
+    fn can_accept(&self) -> bool;
 
     fn reset(&mut self);
 
-    fn get_statistics(&self) -> Vec<PipelineNodeStatistics>;
+    /// Forgets the statistics gathered before `now` but keeps the node's state, e.g. at the end of a warm-up.
+    fn reset_statistics(&mut self, now: Time);
+
+    /// Restarts the node's random stream from `seed`, the `antithetic` stream should be
+    /// drawn through [`Antithetic`](crate::streams::Antithetic).
+    fn reseed(&mut self, seed: u64, antithetic: bool);
+
+    fn get_statistics(&self, now: Time) -> PipelineNodeStatistics;
 }
 
 pub trait IntoPipelineNode<R>
 where
     R: Rng,
 {
-    fn into_node(
-        self: Box<Self>,
-        rand_gen: R,
-        next: Box<dyn PipelineNode>,
-    ) -> Box<dyn PipelineNode>;
+    /// Builds the node drawing from `rand_gen`, an independent stream of the node, fails if its parameters are invalid.
+    fn into_node(self: Box<Self>, rand_gen: R) -> Result<Box<dyn PipelineNode>, PipelineError>;
+}
+
+pub struct NodeContext<'a> {
+    node: usize,
+    can_start: bool,
+    scheduler: &'a mut Scheduler,
+}
+
+impl<'a> NodeContext<'a> {
+    pub(crate) fn new(node: usize, can_start: bool, scheduler: &'a mut Scheduler) -> Self {
+        Self {
+            node,
+            can_start,
+            scheduler,
+        }
+    }
+
+    pub fn now(&self) -> Time {
+        self.scheduler.now()
+    }
+
+    /// Whether the node may start handling a request, it's false while the node blocks
+    /// before service and the next node is full.
+    pub fn can_start(&self) -> bool {
+        self.can_start
+    }
+
+    /// Schedules [`PipelineNode::complete`] to be called with `tag` after `delay`,
+    /// a completion past the largest time stops the simulation with an error.
+    pub fn schedule_completion(&mut self, delay: Time, tag: usize) {
+        self.scheduler.schedule_after(
+            delay,
+            EventKind::Completion {
+                node: self.node,
+                tag,
+            },
+        );
+    }
 }
 
 #[derive(Debug)]
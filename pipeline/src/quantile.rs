@@ -0,0 +1,244 @@
+//! Streaming estimation of quantiles by the P² algorithm, without storing the observations.
+
+use serde::Serialize;
+
+/// Quantiles the sojourn and waiting times are reported at.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Quantiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub p999: f64,
+}
+
+/// P² estimator of several quantiles at once, the extension of Jain and Chlamtac's algorithm
+/// by Raatikainen. Markers are kept at the target probabilities and halfway between them,
+/// so the markers also give a piecewise-linear distribution function.
+#[derive(Debug, Clone)]
+pub struct QuantileSketch {
+    /// Probabilities the markers track, from `0` to `1`.
+    probabilities: Vec<f64>,
+    heights: Vec<f64>,
+    positions: Vec<f64>,
+    desired_positions: Vec<f64>,
+    count: usize,
+}
+
+impl QuantileSketch {
+    /// Creates the sketch tracking the quantiles at `probabilities`, each one in `(0, 1)`.
+    pub fn new(probabilities: &[f64]) -> Self {
+        let mut targets = probabilities
+            .iter()
+            .copied()
+            .filter(|p| *p > 0.0 && *p < 1.0)
+            .collect::<Vec<_>>();
+        targets.sort_by(f64::total_cmp);
+        targets.dedup();
+
+        let mut markers = vec![0.0];
+        let mut previous = 0.0;
+        for p in targets {
+            markers.push((previous + p) / 2.0);
+            markers.push(p);
+            previous = p;
+        }
+        markers.push((previous + 1.0) / 2.0);
+        markers.push(1.0);
+
+        Self {
+            heights: Vec::with_capacity(markers.len()),
+            positions: (1..=markers.len()).map(|i| i as f64).collect(),
+            desired_positions: markers
+                .iter()
+                .map(|p| 1.0 + (markers.len() - 1) as f64 * p)
+                .collect(),
+            probabilities: markers,
+            count: 0,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self {
+            heights: Vec::with_capacity(self.probabilities.len()),
+            positions: (1..=self.probabilities.len()).map(|i| i as f64).collect(),
+            desired_positions: self
+                .probabilities
+                .iter()
+                .map(|p| 1.0 + (self.probabilities.len() - 1) as f64 * p)
+                .collect(),
+            probabilities: std::mem::take(&mut self.probabilities),
+            count: 0,
+        };
+    }
+
+    pub fn push(&mut self, value: f64) {
+        let markers = self.probabilities.len();
+        self.count += 1;
+
+        // the first observations become the markers as they are
+        if self.heights.len() < markers {
+            let i = self.heights.partition_point(|height| *height <= value);
+            self.heights.insert(i, value);
+            return;
+        }
+
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[markers - 1] {
+            self.heights[markers - 1] = value;
+            markers - 2
+        } else {
+            self.heights.partition_point(|height| *height <= value) - 1
+        };
+
+        self.positions[cell + 1..]
+            .iter_mut()
+            .for_each(|position| *position += 1.0);
+        self.desired_positions
+            .iter_mut()
+            .zip(&self.probabilities)
+            .for_each(|(position, p)| *position += p);
+
+        for i in 1..markers - 1 {
+            let difference = self.desired_positions[i] - self.positions[i];
+
+            if (difference >= 1.0 && self.positions[i + 1] - self.positions[i] > 1.0)
+                || (difference <= -1.0 && self.positions[i - 1] - self.positions[i] < -1.0)
+            {
+                let step = difference.signum();
+                let height = self.parabolic(i, step);
+
+                self.heights[i] = if self.heights[i - 1] < height && height < self.heights[i + 1] {
+                    height
+                } else {
+                    self.linear(i, step)
+                };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    /// Estimated quantile at `probability`, interpolated between the markers if it isn't tracked,
+    /// `None` without observations.
+    pub fn quantile(&self, probability: f64) -> Option<f64> {
+        let cdf = self.cdf();
+        let probability = probability.clamp(0.0, 1.0);
+
+        let i = cdf.partition_point(|(_, p)| *p < probability);
+        let (value, p) = *cdf.get(i)?;
+        if i == 0 || p == probability {
+            return Some(value);
+        }
+
+        let (previous_value, previous_p) = cdf[i - 1];
+        Some(
+            previous_value
+                + (value - previous_value) * (probability - previous_p) / (p - previous_p),
+        )
+    }
+
+    /// `None` without observations.
+    pub fn quantiles(&self) -> Option<Quantiles> {
+        Some(Quantiles {
+            p50: self.quantile(0.5)?,
+            p90: self.quantile(0.9)?,
+            p99: self.quantile(0.99)?,
+            p999: self.quantile(0.999)?,
+        })
+    }
+
+    /// Points `(value, probability)` of the estimated distribution function, it's the empirical one
+    /// while there are fewer observations than markers.
+    pub fn cdf(&self) -> Vec<(f64, f64)> {
+        if self.count < self.probabilities.len() {
+            let count = self.heights.len();
+            return self
+                .heights
+                .iter()
+                .enumerate()
+                .map(|(i, height)| (*height, (i + 1) as f64 / count as f64))
+                .collect();
+        }
+
+        self.heights
+            .iter()
+            .zip(&self.probabilities)
+            .map(|(height, p)| (*height, *p))
+            .collect()
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+
+        q[i] + step / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + step) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - step) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let j = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i]
+            + step * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+}
+
+impl Default for QuantileSketch {
+    /// Tracks the deciles and the tail quantiles reported in [`Quantiles`].
+    fn default() -> Self {
+        Self::new(&[
+            0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 0.95, 0.99, 0.999,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Uniform numbers in `[0, 1)` of a linear congruential generator, the same every time.
+    fn uniform(count: usize) -> Vec<f64> {
+        let mut state = 1u64;
+
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 11) as f64 / (1u64 << 53) as f64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn median_is_close_to_the_exact_one() {
+        let mut values = uniform(10_001);
+        let mut sketch = QuantileSketch::new(&[0.5]);
+        values.iter().for_each(|value| sketch.push(*value));
+
+        values.sort_by(f64::total_cmp);
+        let exact = values[values.len() / 2];
+        let median = sketch.quantile(0.5).unwrap();
+        assert_eq!(sketch.count(), values.len());
+        assert!(
+            (median - exact).abs() < 0.01,
+            "expected {exact}, got {median}"
+        );
+    }
+
+    #[test]
+    fn quantiles_of_few_observations_are_exact() {
+        let mut sketch = QuantileSketch::new(&[0.5]);
+        assert_eq!(sketch.quantile(0.5), None);
+
+        [3.0, 1.0, 2.0]
+            .into_iter()
+            .for_each(|value| sketch.push(value));
+        assert_eq!(sketch.quantile(0.0), Some(1.0));
+        assert_eq!(sketch.quantile(1.0), Some(3.0));
+    }
+}
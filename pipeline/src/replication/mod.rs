@@ -0,0 +1,679 @@
+//! Independent replications of a simulation with confidence intervals of the results.
+
+mod normal;
+mod parallel;
+mod student;
+
+pub use self::normal::normal_quantile;
+pub use self::parallel::run_parallel;
+pub use self::student::student_t_quantile;
+
+use crate::{
+    arrival::ArrivalProcess,
+    streams::{stream_seed, Stream},
+    Pipeline, PipelineError, Statistics, Time,
+};
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Display},
+    num::NonZeroUsize,
+};
+
+/// Estimate of a metric over the replications with its confidence interval.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Estimate {
+    pub mean: f64,
+    /// Sample variance of the metric between the replications.
+    pub variance: f64,
+    /// Half of the confidence interval's width, it's infinite for less than two replications.
+    pub half_width: f64,
+    pub replications: usize,
+    /// Fraction of the variance of the mean removed by antithetic or control variates,
+    /// compared with as many independent replications.
+    pub variance_reduction: Option<f64>,
+}
+
+/// Target half-width of the confidence intervals.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precision {
+    Absolute(f64),
+    /// Fraction of the estimated mean.
+    Relative(f64),
+}
+
+/// Runs a pipeline again and again from the empty state, the replication `k` draws from
+/// the streams of the seed [`Stream::Replication`]`(k)` derived from the pipeline's seed.
+/// So the pipelines built with the same seed are compared with common random numbers
+/// replication by replication.
+///
+/// With `antithetic` set the replications go in pairs sharing the seed, the second one of a pair
+/// draws `1 - U` for every uniform `U` of the first one and the pair is one sample of the metrics.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplicationRunner {
+    pub working_time: Time,
+    /// Confidence level of the intervals, e.g. `0.95`.
+    pub level: f64,
+    /// Replications to run before checking the precision.
+    pub replications: usize,
+    /// Stopping rule, the runner goes on until every interval is within the precision
+    /// or `max_replications` are run.
+    pub precision: Option<Precision>,
+    pub max_replications: usize,
+    pub antithetic: bool,
+    /// Known arrival rate, the observed one is the control variate of the average handling time.
+    pub arrival_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplicationReport {
+    pub level: f64,
+    pub replications: usize,
+    /// The replications are antithetic pairs, so the estimates are over the pairs.
+    pub antithetic: bool,
+    pub probability_of_request_delay: Estimate,
+    pub average_handling_time: Estimate,
+    pub throughput: Estimate,
+    pub nodes_idle_probabilities: Vec<Estimate>,
+    pub servers_busy_probabilities: Vec<Vec<Estimate>>,
+    pub classes: Vec<ClassEstimates>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ClassEstimates {
+    pub probability_of_request_delay: Estimate,
+    pub average_handling_time: Estimate,
+    pub throughput: Estimate,
+    pub average_waiting_time: Estimate,
+}
+
+impl Estimate {
+    /// Estimates the mean of the metric from its values in the replications.
+    pub fn from_samples(samples: &[f64], level: f64) -> Self {
+        let replications = samples.len();
+        let mean = samples.iter().sum::<f64>() / replications as f64;
+        let variance = sample_variance(samples.iter().map(|sample| sample - mean));
+
+        Self::new(mean, variance, level, replications)
+    }
+
+    /// Estimates a ratio such as the delay probability as the ratio of the totals, not the mean
+    /// of the ratios. The variance is the delta method's one, so the interval is for the ratio.
+    pub fn from_ratio(numerators: &[f64], denominators: &[f64], level: f64) -> Self {
+        let replications = numerators.len();
+        let denominators_mean = denominators.iter().sum::<f64>() / replications as f64;
+        let mean = numerators.iter().sum::<f64>() / denominators.iter().sum::<f64>();
+
+        let residuals = numerators
+            .iter()
+            .zip(denominators)
+            .map(|(numerator, denominator)| numerator - mean * denominator);
+        let variance = sample_variance(residuals) / denominators_mean.powi(2);
+
+        Self::new(mean, variance, level, replications)
+    }
+
+    /// Estimates the mean of the metric corrected by a control, a quantity observed in the same
+    /// replications whose mean is known: `mean - beta * (controls mean - control_mean)` where
+    /// `beta` is the regression coefficient of the samples on the controls.
+    pub fn from_controlled_samples(
+        samples: &[f64],
+        controls: &[f64],
+        control_mean: f64,
+        level: f64,
+    ) -> Self {
+        let replications = samples.len();
+        let samples_mean = samples.iter().sum::<f64>() / replications as f64;
+        let controls_mean = controls.iter().sum::<f64>() / replications as f64;
+
+        let covariance = samples
+            .iter()
+            .zip(controls)
+            .map(|(sample, control)| (sample - samples_mean) * (control - controls_mean))
+            .sum::<f64>();
+        let control_variance = controls
+            .iter()
+            .map(|control| (control - controls_mean).powi(2))
+            .sum::<f64>();
+
+        // A constant control or too few replications to fit beta leave nothing to correct.
+        if replications < 3 || control_variance == 0.0 {
+            return Self::from_samples(samples, level);
+        }
+
+        let beta = covariance / control_variance;
+        let mean = samples_mean - beta * (controls_mean - control_mean);
+        let variance = samples
+            .iter()
+            .zip(controls)
+            .map(|(sample, control)| {
+                (sample - samples_mean - beta * (control - controls_mean)).powi(2)
+            })
+            .sum::<f64>()
+            / (replications as f64 - 2.0);
+
+        // Fitting beta takes one more degree of freedom than the mean.
+        Self::with_degrees_of_freedom(mean, variance, level, replications, replications - 2)
+    }
+
+    fn new(mean: f64, variance: f64, level: f64, replications: usize) -> Self {
+        Self::with_degrees_of_freedom(
+            mean,
+            variance,
+            level,
+            replications,
+            replications.saturating_sub(1),
+        )
+    }
+
+    fn with_degrees_of_freedom(
+        mean: f64,
+        variance: f64,
+        level: f64,
+        replications: usize,
+        degrees_of_freedom: usize,
+    ) -> Self {
+        if degrees_of_freedom == 0 {
+            return Self {
+                mean,
+                variance: 0.0,
+                half_width: f64::INFINITY,
+                replications,
+                variance_reduction: None,
+            };
+        }
+
+        let quantile = student_t_quantile((1.0 + level) / 2.0, degrees_of_freedom);
+
+        Self {
+            mean,
+            variance,
+            half_width: quantile * (variance / replications as f64).sqrt(),
+            replications,
+            variance_reduction: None,
+        }
+    }
+
+    pub fn interval(&self) -> (f64, f64) {
+        (self.mean - self.half_width, self.mean + self.half_width)
+    }
+
+    pub fn satisfies(&self, precision: Precision) -> bool {
+        self.half_width <= self.target_half_width(precision)
+    }
+
+    /// Replications needed to get the interval within the precision, judging by the current variance.
+    pub fn required_replications(&self, precision: Precision, level: f64) -> usize {
+        let quantile = student_t_quantile((1.0 + level) / 2.0, self.replications.max(2) - 1);
+        let target = self.target_half_width(precision);
+
+        if self.variance == 0.0 {
+            return self.replications;
+        }
+
+        (self.variance * (quantile / target).powi(2)).ceil() as usize
+    }
+
+    /// Variance of the mean, i.e. of the estimator.
+    fn mean_variance(&self) -> f64 {
+        self.variance / self.replications as f64
+    }
+
+    fn target_half_width(&self, precision: Precision) -> f64 {
+        match precision {
+            Precision::Absolute(half_width) => half_width,
+            Precision::Relative(fraction) => fraction * self.mean.abs(),
+        }
+    }
+}
+
+impl ReplicationRunner {
+    pub fn run<ArrivalDistr, Rand>(
+        &self,
+        pipeline: &mut Pipeline<ArrivalDistr, Rand>,
+    ) -> Result<ReplicationReport, PipelineError>
+    where
+        ArrivalDistr: ArrivalProcess + Debug,
+        Rand: Rng + SeedableRng + Debug,
+    {
+        let seed = pipeline.seed();
+        let mut statistics = Vec::with_capacity(self.replications);
+
+        loop {
+            let sample = statistics.len() / self.runs_per_sample();
+            statistics.extend(self.run_sample(pipeline, seed, sample)?);
+
+            if statistics.len() < self.min_replications() {
+                continue;
+            }
+
+            let report = self.report(&statistics);
+            let done = match self.precision {
+                Some(precision) => {
+                    report.satisfies(precision) || statistics.len() >= self.max_replications
+                }
+                None => true,
+            };
+
+            if done {
+                break Ok(report);
+            }
+        }
+    }
+
+    /// Runs the replications like [`ReplicationRunner::run`] on a pipeline per thread,
+    /// every pipeline is built by `build` from the same config, i.e. with the same seed.
+    ///
+    /// The report is the one of [`ReplicationRunner::run`] with as many replications. With
+    /// a precision the replications go in batches sized by the required replications,
+    /// so the last batch may run more of them than needed.
+    pub fn run_parallel<ArrivalDistr, Rand, Build, E>(
+        &self,
+        threads: NonZeroUsize,
+        mut build: Build,
+    ) -> Result<ReplicationReport, E>
+    where
+        ArrivalDistr: ArrivalProcess + Debug + Send,
+        Rand: Rng + SeedableRng + Debug + Send,
+        Build: FnMut() -> Result<Pipeline<ArrivalDistr, Rand>, E>,
+        E: From<PipelineError>,
+    {
+        let mut pipelines = (0..threads.get())
+            .map(|_| build())
+            .collect::<Result<Vec<_>, _>>()?;
+        let seed = pipelines[0].seed();
+        let runs = self.runs_per_sample();
+        let max_samples = self.max_replications.div_ceil(runs);
+
+        let mut statistics = Vec::with_capacity(self.replications);
+        let mut samples = self.min_replications().div_ceil(runs);
+
+        loop {
+            let batch = run_parallel(
+                &mut pipelines,
+                statistics.len() / runs..samples,
+                |pipeline, sample| self.run_sample(pipeline, seed, sample),
+            );
+            for sample in batch {
+                statistics.extend(sample?);
+            }
+
+            let report = self.report(&statistics);
+            let precision = match self.precision {
+                Some(precision) if !report.satisfies(precision) && samples < max_samples => {
+                    precision
+                }
+                _ => break Ok(report),
+            };
+
+            samples = report
+                .required_replications(precision)
+                .clamp(samples + 1, max_samples);
+        }
+    }
+
+    /// Runs the replications of the sample, an antithetic pair of them or a single one.
+    fn run_sample<ArrivalDistr, Rand>(
+        &self,
+        pipeline: &mut Pipeline<ArrivalDistr, Rand>,
+        seed: u64,
+        sample: usize,
+    ) -> Result<Vec<Statistics>, PipelineError>
+    where
+        ArrivalDistr: ArrivalProcess + Debug,
+        Rand: Rng + SeedableRng + Debug,
+    {
+        [false, true]
+            .into_iter()
+            .take(self.runs_per_sample())
+            .map(|antithetic| {
+                pipeline.reseed(stream_seed(seed, Stream::Replication(sample)), antithetic);
+                pipeline.reset();
+                pipeline.work_during(self.working_time)?;
+                Ok(pipeline.get_statistics())
+            })
+            .collect()
+    }
+
+    fn runs_per_sample(&self) -> usize {
+        if self.antithetic {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Replications to run before checking the precision.
+    fn min_replications(&self) -> usize {
+        // Fitting the control takes a degree of freedom more.
+        let min_samples = if self.arrival_rate.is_some() { 3 } else { 2 };
+        self.replications.max(min_samples * self.runs_per_sample())
+    }
+
+    /// Estimates the metrics with the variance reduction of the runner, which is compared with
+    /// treating the replications as independent ones.
+    fn report(&self, statistics: &[Statistics]) -> ReplicationReport {
+        if !self.antithetic && self.arrival_rate.is_none() {
+            return ReplicationReport::new(statistics, self.level);
+        }
+
+        let mut report = if self.antithetic {
+            ReplicationReport::antithetic(statistics, self.level)
+        } else {
+            ReplicationReport::new(statistics, self.level)
+        };
+
+        if let Some(arrival_rate) = self.arrival_rate {
+            report.control_arrival_rate(statistics, arrival_rate);
+        }
+
+        report.compare_with(&ReplicationReport::new(statistics, self.level));
+        report
+    }
+}
+
+impl ReplicationReport {
+    /// Estimates the metrics from the statistics of independent replications.
+    pub fn new(statistics: &[Statistics], level: f64) -> Self {
+        Self::from_samples(statistics, level, false)
+    }
+
+    /// Estimates the metrics from the statistics of antithetic pairs of replications,
+    /// the pairs go one after the other.
+    pub fn antithetic(statistics: &[Statistics], level: f64) -> Self {
+        Self::from_samples(statistics, level, true)
+    }
+
+    fn from_samples(statistics: &[Statistics], level: f64, antithetic: bool) -> Self {
+        let collect = |metric: &dyn Fn(&Statistics) -> f64| -> Vec<f64> {
+            samples(statistics, antithetic)
+                .map(|sample| sample.iter().map(metric).sum())
+                .collect()
+        };
+
+        let working_times = collect(&|stats| f64::from(stats.working_time));
+        let completed = collect(&|stats| stats.completed_requests_number);
+        let nodes_count = statistics.iter().map(|stats| stats.nodes.len()).max();
+        let classes_count = statistics.iter().map(|stats| stats.classes.len()).max();
+
+        let nodes_idle_probabilities = (0..nodes_count.unwrap_or_default())
+            .map(|i| {
+                let idle_times = collect(&|stats| stats.nodes.get(i).map_or(0.0, |n| n.idle_time));
+                Estimate::from_ratio(&idle_times, &working_times, level)
+            })
+            .collect();
+
+        let servers_busy_probabilities = (0..nodes_count.unwrap_or_default())
+            .map(|i| {
+                let servers_count = statistics
+                    .iter()
+                    .filter_map(|stats| Some(stats.nodes.get(i)?.servers.len()))
+                    .max()
+                    .unwrap_or_default();
+
+                (0..servers_count)
+                    .map(|j| {
+                        let busy_times = collect(&|stats| {
+                            stats
+                                .nodes
+                                .get(i)
+                                .and_then(|node| node.servers.get(j))
+                                .map_or(0.0, |server| server.busy_time)
+                        });
+                        Estimate::from_ratio(&busy_times, &working_times, level)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let classes = (0..classes_count.unwrap_or_default())
+            .map(|i| {
+                let class = |stats: &Statistics| stats.classes.get(i).copied().unwrap_or_default();
+                let completed = collect(&|stats| class(stats).completed_requests_number);
+
+                ClassEstimates {
+                    probability_of_request_delay: Estimate::from_ratio(
+                        &collect(&|stats| {
+                            let class = class(stats);
+                            class.delayed_requests_count + class.lost_requests_count
+                        }),
+                        &collect(&|stats| class(stats).requests_number),
+                        level,
+                    ),
+                    average_handling_time: Estimate::from_ratio(
+                        &collect(&|stats| {
+                            let class = class(stats);
+                            total(class.average_handling_time, class.completed_requests_number)
+                        }),
+                        &completed,
+                        level,
+                    ),
+                    throughput: Estimate::from_ratio(&completed, &working_times, level),
+                    average_waiting_time: Estimate::from_ratio(
+                        &collect(&|stats| {
+                            let class = class(stats);
+                            total(class.average_waiting_time, class.completed_requests_number)
+                        }),
+                        &completed,
+                        level,
+                    ),
+                }
+            })
+            .collect();
+
+        Self {
+            level,
+            replications: statistics.len(),
+            antithetic,
+            probability_of_request_delay: Estimate::from_ratio(
+                &collect(&|stats| stats.delayed_requests_count),
+                &collect(&|stats| stats.requests_number),
+                level,
+            ),
+            average_handling_time: Estimate::from_ratio(
+                &collect(&|stats| {
+                    total(stats.average_handling_time, stats.completed_requests_number)
+                }),
+                &completed,
+                level,
+            ),
+            throughput: Estimate::from_ratio(&completed, &working_times, level),
+            nodes_idle_probabilities,
+            servers_busy_probabilities,
+            classes,
+        }
+    }
+
+    /// Corrects the average handling time with the observed arrival rate, whose mean is known,
+    /// as the control variate. The statistics are the ones the report is estimated from.
+    pub fn control_arrival_rate(&mut self, statistics: &[Statistics], arrival_rate: f64) {
+        let handling_times: Vec<_> = samples(statistics, self.antithetic)
+            .map(|sample| {
+                sample
+                    .iter()
+                    .map(|stats| {
+                        total(stats.average_handling_time, stats.completed_requests_number)
+                    })
+                    .sum::<f64>()
+            })
+            .collect();
+        let completed: Vec<_> = samples(statistics, self.antithetic)
+            .map(|sample| {
+                sample
+                    .iter()
+                    .map(|stats| stats.completed_requests_number)
+                    .sum()
+            })
+            .collect();
+        let arrival_rates: Vec<_> = samples(statistics, self.antithetic)
+            .map(|sample| {
+                let requests = sample
+                    .iter()
+                    .map(|stats| stats.requests_number)
+                    .sum::<f64>();
+                let working_time = sample
+                    .iter()
+                    .map(|stats| f64::from(stats.working_time))
+                    .sum::<f64>();
+                requests / working_time
+            })
+            .collect();
+
+        self.average_handling_time = Estimate::from_controlled_samples(
+            &linearized_ratio(&handling_times, &completed),
+            &arrival_rates,
+            arrival_rate,
+            self.level,
+        );
+    }
+
+    /// Sets the variance reduction of every estimate compared with the one of the baseline,
+    /// usually the report of the same replications treated as independent ones.
+    pub fn compare_with(&mut self, baseline: &ReplicationReport) {
+        for (estimate, base) in self.estimates_mut().into_iter().zip(baseline.estimates()) {
+            let base_variance = base.1.mean_variance();
+            estimate.variance_reduction = (base_variance > 0.0 && base_variance.is_finite())
+                .then(|| 1.0 - estimate.mean_variance() / base_variance);
+        }
+    }
+
+    /// Every estimate with the name of its metric.
+    pub fn estimates(&self) -> Vec<(String, Estimate)> {
+        let mut estimates = vec![
+            (
+                "probability of request delay".to_owned(),
+                self.probability_of_request_delay,
+            ),
+            (
+                "average handling time".to_owned(),
+                self.average_handling_time,
+            ),
+            ("throughput".to_owned(), self.throughput),
+        ];
+
+        for (i, estimate) in self.nodes_idle_probabilities.iter().enumerate() {
+            estimates.push((format!("node {i} idle probability"), *estimate));
+        }
+
+        for (i, servers) in self.servers_busy_probabilities.iter().enumerate() {
+            for (j, estimate) in servers.iter().enumerate() {
+                estimates.push((format!("node {i} server {j} busy probability"), *estimate));
+            }
+        }
+
+        for (i, class) in self.classes.iter().enumerate() {
+            estimates.push((
+                format!("class {i} probability of request delay"),
+                class.probability_of_request_delay,
+            ));
+            estimates.push((
+                format!("class {i} average handling time"),
+                class.average_handling_time,
+            ));
+            estimates.push((format!("class {i} throughput"), class.throughput));
+            estimates.push((
+                format!("class {i} average waiting time"),
+                class.average_waiting_time,
+            ));
+        }
+
+        estimates
+    }
+
+    /// Every estimate in the order of [`ReplicationReport::estimates`].
+    fn estimates_mut(&mut self) -> Vec<&mut Estimate> {
+        let mut estimates = vec![
+            &mut self.probability_of_request_delay,
+            &mut self.average_handling_time,
+            &mut self.throughput,
+        ];
+        estimates.extend(self.nodes_idle_probabilities.iter_mut());
+        estimates.extend(self.servers_busy_probabilities.iter_mut().flatten());
+
+        for class in &mut self.classes {
+            estimates.push(&mut class.probability_of_request_delay);
+            estimates.push(&mut class.average_handling_time);
+            estimates.push(&mut class.throughput);
+            estimates.push(&mut class.average_waiting_time);
+        }
+
+        estimates
+    }
+
+    pub fn satisfies(&self, precision: Precision) -> bool {
+        self.estimates()
+            .iter()
+            .all(|(_, estimate)| estimate.satisfies(precision))
+    }
+
+    /// Replications needed for every interval to get within the precision.
+    pub fn required_replications(&self, precision: Precision) -> usize {
+        self.estimates()
+            .iter()
+            .map(|(_, estimate)| estimate.required_replications(precision, self.level))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Display for ReplicationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} replications", self.replications)?;
+        if self.antithetic {
+            write!(f, " in {} antithetic pairs", self.replications / 2)?;
+        }
+        writeln!(f, ", {}% confidence intervals", self.level * 100.0)?;
+        writeln!(
+            f,
+            "{:<40} {:>12} {:>12} {:>12} {:>12}",
+            "metric", "mean", "half-width", "variance", "reduction"
+        )?;
+
+        for (metric, estimate) in self.estimates() {
+            let reduction = estimate
+                .variance_reduction
+                .map_or("-".to_owned(), |reduction| {
+                    format!("{:.1}%", reduction * 100.0)
+                });
+            writeln!(
+                f,
+                "{:<40} {:>12.5} {:>12.5} {:>12.5} {:>12}",
+                metric, estimate.mean, estimate.half_width, estimate.variance, reduction
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Sum of a metric over the completed requests, it's zero when none is completed and the average
+/// is undefined.
+fn total(average: Option<f64>, completed_requests_number: f64) -> f64 {
+    average.map_or(0.0, |average| average * completed_requests_number)
+}
+
+/// Statistics of every sample, a replication or an antithetic pair of them.
+fn samples(statistics: &[Statistics], antithetic: bool) -> std::slice::Chunks<'_, Statistics> {
+    statistics.chunks(if antithetic { 2 } else { 1 })
+}
+
+/// Linearization of the ratio of the totals, the values average to the ratio and their
+/// sample variance is the delta method's one of [`Estimate::from_ratio`].
+fn linearized_ratio(numerators: &[f64], denominators: &[f64]) -> Vec<f64> {
+    let denominators_mean = denominators.iter().sum::<f64>() / denominators.len() as f64;
+    let ratio = numerators.iter().sum::<f64>() / denominators.iter().sum::<f64>();
+
+    numerators
+        .iter()
+        .zip(denominators)
+        .map(|(numerator, denominator)| {
+            ratio + (numerator - ratio * denominator) / denominators_mean
+        })
+        .collect()
+}
+
+fn sample_variance(deviations: impl ExactSizeIterator<Item = f64>) -> f64 {
+    let count = deviations.len();
+    deviations.map(|deviation| deviation.powi(2)).sum::<f64>() / (count as f64 - 1.0)
+}
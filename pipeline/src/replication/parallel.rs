@@ -0,0 +1,45 @@
+use std::{ops::Range, thread};
+
+/// Runs `replicate` for every index of the range on its own thread per pipeline, the pipeline
+/// of the thread `t` takes the indices `start + t`, `start + t + threads` and so on.
+///
+/// The results are in the order of the indices whatever thread has run them, so they're
+/// deterministic as long as a replication depends on its index only, e.g. it reseeds
+/// the pipeline from [`Stream::Replication`](crate::streams::Stream::Replication) first.
+pub fn run_parallel<P, T, F>(pipelines: &mut [P], indices: Range<usize>, replicate: F) -> Vec<T>
+where
+    P: Send,
+    T: Send,
+    F: Fn(&mut P, usize) -> T + Sync,
+{
+    let threads = pipelines.len().max(1);
+    let replicate = &replicate;
+
+    let mut results: Vec<(usize, T)> = thread::scope(|scope| {
+        let handles: Vec<_> = pipelines
+            .iter_mut()
+            .enumerate()
+            .map(|(t, pipeline)| {
+                let indices = indices.clone();
+                scope.spawn(move || {
+                    (indices.start + t..indices.end)
+                        .step_by(threads)
+                        .map(|index| (index, replicate(pipeline, index)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
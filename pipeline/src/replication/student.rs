@@ -0,0 +1,126 @@
+use crate::distr::special::ln_gamma;
+
+/// Quantile of the Student's t-distribution, i.e. `t` such that `P(T <= t) = probability`,
+/// found by bisection of the distribution function.
+pub fn student_t_quantile(probability: f64, degrees_of_freedom: usize) -> f64 {
+    let degrees_of_freedom = degrees_of_freedom as f64;
+
+    if probability < 0.5 {
+        return -student_t_quantile(1.0 - probability, degrees_of_freedom as usize);
+    }
+
+    let mut low = 0.0;
+    let mut high = 1.0;
+    while student_t_cdf(high, degrees_of_freedom) < probability {
+        low = high;
+        high *= 2.0;
+    }
+
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+
+        if student_t_cdf(middle, degrees_of_freedom) < probability {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+fn student_t_cdf(t: f64, degrees_of_freedom: f64) -> f64 {
+    let x = degrees_of_freedom / (degrees_of_freedom + t * t);
+    let tail = 0.5 * incomplete_beta(degrees_of_freedom / 2.0, 0.5, x);
+
+    if t >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // the continued fraction converges fast only on one side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function, evaluated with the modified Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut fraction = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            fraction *= d * c;
+        }
+
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+
+    fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn quantiles_match_the_tables() {
+        assert_close(student_t_quantile(0.975, 9), 2.262157);
+        assert_close(student_t_quantile(0.975, 1), 12.706205);
+        assert_close(student_t_quantile(0.95, 30), 1.697261);
+        assert_close(student_t_quantile(0.995, 4), 4.604095);
+    }
+
+    #[test]
+    fn quantiles_are_symmetric() {
+        assert_close(student_t_quantile(0.025, 9), -2.262157);
+        assert_close(student_t_quantile(0.5, 9), 0.0);
+    }
+}
@@ -1,7 +1,86 @@
-use crate::time::Time;
+use crate::{network::NodeId, time::Time};
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Request {
+    pub id: usize,
+    pub class: usize,
+    /// `0` is the highest priority.
+    pub priority: usize,
     pub arrival_time: Time,
     pub leaving_time: Time,
+    /// Handling time given by the arrival process, it's used by the first station instead of sampling.
+    pub service_time: Option<Time>,
+    /// Stations the request has passed in the order of passing them.
+    pub visits: Vec<Visit>,
+}
+
+/// Timestamps of a request passing a station.
+#[derive(Debug, Clone, Copy)]
+pub struct Visit {
+    pub node: NodeId,
+    /// When the request has entered the station's buffer.
+    pub entered_at: Time,
+    /// When the station has started handling the request for the first time.
+    pub started_at: Option<Time>,
+    pub finished_at: Option<Time>,
+    /// When the next node has accepted the request, it's later than `finished_at` if the station was blocked.
+    pub left_at: Option<Time>,
+}
+
+impl Request {
+    /// Time from arriving to leaving the pipeline.
+    pub fn handling_time(&self) -> Time {
+        self.leaving_time.saturating_sub(self.arrival_time)
+    }
+
+    /// Records the start of the handling at the current station, the restarts after preemptions
+    /// don't change it.
+    pub fn start_service(&mut self, now: Time) {
+        if let Some(visit) = self.visits.last_mut() {
+            visit.started_at.get_or_insert(now);
+        }
+    }
+
+    pub fn finish_service(&mut self, now: Time) {
+        if let Some(visit) = self.visits.last_mut() {
+            visit.finished_at = Some(now);
+        }
+    }
+
+    pub(crate) fn enter_station(&mut self, node: NodeId, now: Time) {
+        self.visits.push(Visit {
+            node,
+            entered_at: now,
+            started_at: None,
+            finished_at: None,
+            left_at: None,
+        });
+    }
+
+    pub(crate) fn leave_station(&mut self, now: Time) {
+        if let Some(visit) = self.visits.last_mut() {
+            visit.left_at = Some(now);
+        }
+    }
+}
+
+impl Visit {
+    /// Time in the buffer before the handling has started.
+    pub fn waiting_time(&self) -> Option<Time> {
+        Some(self.started_at?.saturating_sub(self.entered_at))
+    }
+
+    /// Time from the start of the handling to its end, including the interruptions by preemptions.
+    pub fn service_time(&self) -> Option<Time> {
+        Some(self.finished_at?.saturating_sub(self.started_at?))
+    }
+
+    /// Time the handled request has been held because the next node was full.
+    pub fn blocking_time(&self) -> Option<Time> {
+        Some(self.left_at?.saturating_sub(self.finished_at?))
+    }
+
+    pub fn sojourn_time(&self) -> Option<Time> {
+        Some(self.left_at?.saturating_sub(self.entered_at))
+    }
 }
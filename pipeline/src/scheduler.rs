@@ -0,0 +1,177 @@
+use crate::{Time, TimeError};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum EventKind {
+    Arrival {
+        class: usize,
+        priority: usize,
+        service_time: Option<Time>,
+    },
+    Completion {
+        node: usize,
+        tag: usize,
+    },
+    Unblock {
+        node: usize,
+        tag: usize,
+    },
+    WarmUpEnd,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Event {
+    pub time: Time,
+    pub kind: EventKind,
+    sequence: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Scheduler {
+    now: Time,
+    events: BinaryHeap<Event>,
+    sequence: usize,
+    /// Error of scheduling an event past the largest time, the simulation can't go on after it.
+    error: Option<TimeError>,
+}
+
+impl Scheduler {
+    pub fn now(&self) -> Time {
+        self.now
+    }
+
+    pub fn clear(&mut self) {
+        self.now = Time::ZERO;
+        self.events.clear();
+        self.sequence = 0;
+        self.error = None;
+    }
+
+    pub fn schedule(&mut self, time: Time, kind: EventKind) {
+        self.sequence += 1;
+        self.events.push(Event {
+            time,
+            kind,
+            sequence: self.sequence,
+        });
+    }
+
+    /// Schedules the event `delay` after now, an event past the largest time isn't scheduled
+    /// and its error is kept for [`Scheduler::take_error`].
+    pub fn schedule_after(&mut self, delay: Time, kind: EventKind) {
+        match self.now.checked_add(delay) {
+            Ok(time) => self.schedule(time, kind),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
+    }
+
+    pub fn take_error(&mut self) -> Option<TimeError> {
+        self.error.take()
+    }
+
+    /// Pops the earliest event that happens no later than `until` and moves the clock to it.
+    pub fn pop_until(&mut self, until: Time) -> Option<Event> {
+        if self.events.peek()?.time > until {
+            return None;
+        }
+
+        let event = self.events.pop()?;
+        self.now = event.time;
+
+        Some(event)
+    }
+
+    pub fn advance_to(&mut self, time: Time) {
+        if time > self.now {
+            self.now = time;
+        }
+    }
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Event {}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Event {
+    // `BinaryHeap` is a max-heap, so the order is reversed to pop the earliest event first,
+    // events scheduled for the same time are popped in the order they were scheduled.
+    fn cmp(&self, other: &Self) -> Ordering {
+        f64::from(other.time)
+            .total_cmp(&f64::from(self.time))
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: f64) -> Time {
+        Time::new(value).unwrap()
+    }
+
+    fn completion(node: usize) -> EventKind {
+        EventKind::Completion { node, tag: 0 }
+    }
+
+    #[test]
+    fn events_pop_by_time_then_in_scheduling_order() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(time(2.0), completion(0));
+        scheduler.schedule(time(1.0), completion(1));
+        scheduler.schedule(time(2.0), completion(2));
+        scheduler.schedule(time(1.0), completion(3));
+        scheduler.schedule(time(2.0), completion(4));
+
+        let mut popped = Vec::new();
+        while let Some(event) = scheduler.pop_until(time(10.0)) {
+            assert_eq!(scheduler.now(), event.time);
+            popped.push(event.kind);
+        }
+
+        assert_eq!(popped, [1, 3, 0, 2, 4].map(completion));
+    }
+
+    #[test]
+    fn events_after_the_limit_wait() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(time(1.0), completion(0));
+        scheduler.schedule(time(3.0), completion(1));
+
+        assert_eq!(
+            scheduler.pop_until(time(2.0)).map(|event| event.kind),
+            Some(completion(0))
+        );
+        assert!(scheduler.pop_until(time(2.0)).is_none());
+
+        scheduler.advance_to(time(2.0));
+        assert_eq!(scheduler.now(), time(2.0));
+        assert_eq!(
+            scheduler.pop_until(time(3.0)).map(|event| event.kind),
+            Some(completion(1))
+        );
+    }
+
+    #[test]
+    fn scheduling_past_the_largest_time_fails() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(time(f64::MAX), completion(0));
+        scheduler.pop_until(time(f64::MAX));
+
+        scheduler.schedule_after(time(f64::MAX), completion(1));
+        assert!(scheduler.take_error().is_some());
+        assert!(scheduler.pop_until(time(f64::MAX)).is_none());
+    }
+}
@@ -1,62 +1,157 @@
-use std::ops::{AddAssign, DivAssign};
+use std::fmt::Display;
 
-use crate::{node::PipelineNodeStatistics, Time};
+use serde::Serialize;
 
-#[derive(Default, Debug)]
+use crate::{node::PipelineNodeStatistics, quantile::Quantiles, Time};
+
+/// Statistics of a run, the ratios are `None` while they're undefined, e.g. the average handling time
+/// before any request is handled.
+#[derive(Default, Debug, Serialize)]
 pub struct Statistics {
     pub working_time: Time,
-    pub requests_number: f32,
-    pub handled_requests_number: f32,
-    pub delayed_requests_count: f32,
-    pub probability_of_request_delay: f32,
-    pub average_handling_time: f32,
+    pub requests_number: f64,
+    /// Requests accepted by the pipeline.
+    pub handled_requests_number: f64,
+    /// Requests that have left the pipeline, the averages over the requests are over them.
+    pub completed_requests_number: f64,
+    pub delayed_requests_count: f64,
+    pub probability_of_request_delay: Option<f64>,
+    pub average_handling_time: Option<f64>,
+    /// Estimated quantiles of the time from arriving to leaving the pipeline.
+    pub handling_time_quantiles: Option<Quantiles>,
+    /// Points `(time, probability)` of the estimated distribution function of the handling time,
+    /// it isn't averaged over runs.
+    pub handling_time_cdf: Vec<(f64, f64)>,
     pub nodes: Vec<PipelineNodeStatistics>,
+    pub classes: Vec<ClassStatistics>,
+}
+
+/// Statistics of the requests of one class, `probability_of_request_delay` is the probability
+/// that a request of the class is lost, either it isn't accepted by the pipeline or a station
+/// drops it later.
+#[derive(Default, Debug, Clone, Copy, Serialize)]
+pub struct ClassStatistics {
+    pub requests_number: f64,
+    /// Requests accepted by the pipeline and not dropped by a station.
+    pub handled_requests_number: f64,
+    pub completed_requests_number: f64,
+    pub delayed_requests_count: f64,
+    /// Accepted requests dropped by a station.
+    pub lost_requests_count: f64,
+    pub probability_of_request_delay: Option<f64>,
+    pub average_handling_time: Option<f64>,
+    pub throughput: Option<f64>,
+    /// Average total time a handled request of the class has waited in the buffers of the stations.
+    pub average_waiting_time: Option<f64>,
 }
 
+/// The probabilities of the nodes are `None` if the working time is zero.
 impl Statistics {
-    pub fn get_nodes_idle_time_probabilities(&self) -> Vec<f32> {
-        self.nodes
-            .iter()
-            .map(|n| n.idle_time / f32::from(self.working_time))
-            .collect()
+    pub fn get_nodes_idle_time_probabilities(&self) -> Option<Vec<f64>> {
+        let working_time = self.defined_working_time()?;
+
+        Some(
+            self.nodes
+                .iter()
+                .map(|n| n.idle_time / working_time)
+                .collect(),
+        )
+    }
+
+    pub fn get_nodes_blocked_time_probabilities(&self) -> Option<Vec<f64>> {
+        let working_time = self.defined_working_time()?;
+
+        Some(
+            self.nodes
+                .iter()
+                .map(|n| n.blocked_time / working_time)
+                .collect(),
+        )
+    }
+
+    pub fn get_servers_busy_time_probabilities(&self) -> Option<Vec<Vec<f64>>> {
+        let working_time = self.defined_working_time()?;
+
+        Some(
+            self.nodes
+                .iter()
+                .map(|n| {
+                    n.servers
+                        .iter()
+                        .map(|s| s.busy_time / working_time)
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Completed requests per unit of the working time.
+    pub fn throughput(&self) -> Option<f64> {
+        ratio(self.completed_requests_number, f64::from(self.working_time))
+    }
+
+    fn defined_working_time(&self) -> Option<f64> {
+        let working_time = f64::from(self.working_time);
+        (working_time > 0.0).then_some(working_time)
     }
 }
 
-impl AddAssign<Statistics> for Statistics {
-    fn add_assign(&mut self, rhs: Statistics) {
-        self.working_time += rhs.working_time;
-        self.handled_requests_number += rhs.handled_requests_number;
-        self.requests_number += rhs.requests_number;
-        self.delayed_requests_count += rhs.delayed_requests_count;
-        self.probability_of_request_delay += rhs.probability_of_request_delay;
-        self.average_handling_time += rhs.average_handling_time;
-
-        if self.nodes.len() < rhs.nodes.len() {
-            let additional_nodes = [PipelineNodeStatistics::default()]
-                .into_iter()
-                .cycle()
-                .take(rhs.nodes.len() - self.nodes.len());
-
-            self.nodes.extend(additional_nodes);
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} requests during {:.5}",
+            self.requests_number,
+            f64::from(self.working_time)
+        )?;
+        writeln!(f, "{:<28} {:>12}", "metric", "value")?;
+
+        let quantiles = self.handling_time_quantiles;
+        let rows = [
+            (
+                "probability of request delay",
+                self.probability_of_request_delay,
+            ),
+            ("average handling time", self.average_handling_time),
+            ("handling time p50", quantiles.map(|q| q.p50)),
+            ("handling time p90", quantiles.map(|q| q.p90)),
+            ("handling time p99", quantiles.map(|q| q.p99)),
+            ("throughput", self.throughput()),
+        ];
+        for (metric, value) in rows {
+            writeln!(f, "{:<28} {:>12}", metric, defined(value))?;
         }
 
-        for i in 0..rhs.nodes.len() {
-            self.nodes[i].idle_time += rhs.nodes[i].idle_time;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "node", "utilization", "queue", "sojourn", "throughput", "blocked", "lost"
+        )?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+                i,
+                defined(node.utilization),
+                defined(node.average_queue_length),
+                defined(node.average_sojourn_time),
+                defined(node.throughput),
+                node.blocked_arrivals_count,
+                node.lost_requests_count
+            )?;
         }
+
+        Ok(())
     }
 }
 
-impl DivAssign<f32> for Statistics {
-    fn div_assign(&mut self, rhs: f32) {
-        self.working_time = Time::from(f32::from(self.working_time) / rhs);
-        self.handled_requests_number /= rhs;
-        self.requests_number /= rhs;
-        self.delayed_requests_count /= rhs;
-        self.probability_of_request_delay /= rhs;
-        self.average_handling_time /= rhs;
-
-        for i in 0..self.nodes.len() {
-            self.nodes[i].idle_time /= rhs;
-        }
-    }
+/// The value for a table, `-` if it's undefined.
+fn defined(value: Option<f64>) -> String {
+    value.map_or("-".to_owned(), |value| format!("{value:.5}"))
+}
+
+/// `None` if the denominator is zero.
+pub(crate) fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    (denominator != 0.0).then(|| numerator / denominator)
 }
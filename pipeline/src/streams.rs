@@ -0,0 +1,95 @@
+//! Independent random streams derived from one seed.
+//!
+//! Every stream is identified by what it feeds, so two scenarios built from the same seed draw
+//! the same arrivals and the same handling times of a node, i.e. they're compared with common
+//! random numbers.
+
+use rand::{RngCore, SeedableRng};
+
+/// What a random stream feeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stream {
+    Arrivals,
+    /// Choice of the routes of the routers.
+    Routing,
+    /// Handling times of the node with the index.
+    Node(usize),
+    /// Seed of the replication with the index, the streams of the replication are derived from it.
+    Replication(usize),
+}
+
+/// Seed of the stream, it's mixed by SplitMix64 so the seeds of the streams are unrelated
+/// even for consecutive indices.
+pub fn stream_seed(seed: u64, stream: Stream) -> u64 {
+    let (kind, index) = match stream {
+        Stream::Arrivals => (0, 0),
+        Stream::Routing => (1, 0),
+        Stream::Node(index) => (2, index as u64),
+        Stream::Replication(index) => (3, index as u64),
+    };
+
+    split_mix(split_mix(split_mix(seed) ^ kind) ^ index)
+}
+
+/// Generator of the stream.
+pub fn stream<R: SeedableRng>(seed: u64, stream: Stream) -> R {
+    R::seed_from_u64(stream_seed(seed, stream))
+}
+
+/// Generator giving the complements of the wrapped generator's bits if `complement` is set,
+/// so every uniform `U` it produces becomes `1 - U`. The inverse-transform samples of a stream
+/// and of its complement are negatively correlated, i.e. they're antithetic variates.
+#[derive(Debug, Clone)]
+pub struct Antithetic<R> {
+    rng: R,
+    complement: bool,
+}
+
+impl<R> Antithetic<R> {
+    pub fn new(rng: R, complement: bool) -> Self {
+        Self { rng, complement }
+    }
+}
+
+impl<R: RngCore> RngCore for Antithetic<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        if self.complement {
+            !value
+        } else {
+            value
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.rng.next_u64();
+        if self.complement {
+            !value
+        } else {
+            value
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        if self.complement {
+            dest.iter_mut().for_each(|byte| *byte = !*byte);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)?;
+        if self.complement {
+            dest.iter_mut().for_each(|byte| *byte = !*byte);
+        }
+
+        Ok(())
+    }
+}
+
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
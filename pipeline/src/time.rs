@@ -1,9 +1,17 @@
-use std::ops;
+use serde::Serialize;
+use std::fmt;
 
-#[derive(Clone, Copy, PartialEq, PartialOrd, Default, Debug)]
-pub struct Time(f32);
+#[derive(Clone, Copy, PartialEq, PartialOrd, Default, Debug, Serialize)]
+pub struct Time(f64);
 
-impl From<Time> for f32 {
+/// Error of a conversion or an operation whose result isn't a valid time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeError {
+    Negative(f64),
+    NotFinite(f64),
+}
+
+impl From<Time> for f64 {
     fn from(value: Time) -> Self {
         value.0
     }
@@ -11,46 +19,55 @@ impl From<Time> for f32 {
 
 impl Time {
     pub const ZERO: Time = Time(0.0);
-}
 
-impl From<f32> for Time {
-    fn from(value: f32) -> Self {
+    pub fn new(value: f64) -> Result<Self, TimeError> {
+        if !value.is_finite() {
+            return Err(TimeError::NotFinite(value));
+        }
+
         if value < 0.0 {
-            panic!("time can't be negative");
+            return Err(TimeError::Negative(value));
         }
 
-        Self(value)
+        Ok(Self(value))
     }
-}
-
-impl ops::Sub<Time> for Time {
-    type Output = Self;
 
-    fn sub(self, rhs: Time) -> Self::Output {
-        if self.0 < rhs.0 {
-            panic!("time can't be negative");
+    /// The time nearest to `value`: the negative values and NaN become zero and the values
+    /// past the largest finite time become it.
+    pub fn saturating(value: f64) -> Self {
+        if value.is_nan() {
+            return Self::ZERO;
         }
 
-        Self(self.0 - rhs.0)
+        Self(value.clamp(0.0, f64::MAX))
     }
-}
 
-impl ops::SubAssign<Time> for Time {
-    fn sub_assign(&mut self, rhs: Time) {
-        self.0 -= rhs.0;
+    pub fn checked_add(self, rhs: Time) -> Result<Time, TimeError> {
+        Self::new(self.0 + rhs.0)
     }
-}
 
-impl ops::Add<Time> for Time {
-    type Output = Self;
+    pub fn checked_sub(self, rhs: Time) -> Result<Time, TimeError> {
+        Self::new(self.0 - rhs.0)
+    }
 
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0)
+    /// Sum of the times, the largest finite time if it overflows.
+    pub fn saturating_add(self, rhs: Time) -> Time {
+        Self::saturating(self.0 + rhs.0)
+    }
+
+    /// Difference of the times, zero if `rhs` is later than `self`.
+    pub fn saturating_sub(self, rhs: Time) -> Time {
+        Self::saturating(self.0 - rhs.0)
     }
 }
 
-impl ops::AddAssign<Time> for Time {
-    fn add_assign(&mut self, rhs: Time) {
-        self.0 += rhs.0;
+impl fmt::Display for TimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeError::Negative(value) => write!(f, "time can't be negative, got {value}"),
+            TimeError::NotFinite(value) => write!(f, "time must be finite, got {value}"),
+        }
     }
 }
+
+impl std::error::Error for TimeError {}
@@ -0,0 +1,176 @@
+//! Detection of the warm-up period, the start-up bias of the runs from the empty state.
+
+use crate::{arrival::ArrivalProcess, Pipeline, PipelineError, Request, Time};
+use rand::Rng;
+use std::fmt::Debug;
+
+/// How to find where the sojourn times of the handled requests settle down.
+#[derive(Debug, Clone, Copy)]
+pub enum WarmUpDetection {
+    /// Welch's method: the sojourn times are averaged over the replications and smoothed with
+    /// a moving average of `2 * window + 1` values. The warm-up ends when the average first gets
+    /// within `tolerance` of the level of its second half, as a fraction of the level.
+    Welch {
+        replications: usize,
+        window: usize,
+        tolerance: f64,
+    },
+    /// MSER-5: truncation of a single run minimizing the marginal standard error of the rest,
+    /// computed on the means of batches of five.
+    Mser5,
+}
+
+impl WarmUpDetection {
+    /// Runs pilot simulations of `working_time` and returns the detected warm-up,
+    /// the pipeline is reset afterwards and keeps its warm-up.
+    pub fn detect<ArrivalDistr, Rand>(
+        &self,
+        pipeline: &mut Pipeline<ArrivalDistr, Rand>,
+        working_time: Time,
+    ) -> Result<Time, PipelineError>
+    where
+        ArrivalDistr: ArrivalProcess + Debug,
+        Rand: Rng + Debug,
+    {
+        let warm_up = pipeline.warm_up();
+        pipeline.set_warm_up(Time::ZERO);
+
+        let mut run = || {
+            pipeline.reset();
+            pipeline.work_during(working_time)
+        };
+
+        let detected = (|| match *self {
+            WarmUpDetection::Welch {
+                replications,
+                window,
+                tolerance,
+            } => {
+                let runs = (0..replications)
+                    .map(|_| run())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let series = runs.iter().map(|requests| sojourn_times(requests));
+                let deleted = welch(&series.collect::<Vec<_>>(), window, tolerance);
+
+                let arrival_times = runs
+                    .iter()
+                    .filter_map(|requests| requests.get(deleted))
+                    .map(|request| f64::from(request.arrival_time))
+                    .collect::<Vec<_>>();
+                Ok(Time::saturating(
+                    arrival_times.iter().sum::<f64>() / arrival_times.len().max(1) as f64,
+                ))
+            }
+            WarmUpDetection::Mser5 => {
+                let requests = run()?;
+                let deleted = mser5(&sojourn_times(&requests));
+
+                Ok(requests
+                    .get(deleted)
+                    .map_or(Time::ZERO, |request| request.arrival_time))
+            }
+        })();
+
+        // the warm-up is restored even if a pilot run fails
+        pipeline.set_warm_up(warm_up);
+        pipeline.reset();
+
+        detected
+    }
+}
+
+/// Number of the leading observations to delete by Welch's method, the replications are cut
+/// to the shortest one.
+pub fn welch(series: &[Vec<f64>], window: usize, tolerance: f64) -> usize {
+    let length = series.iter().map(Vec::len).min().unwrap_or_default();
+    if length == 0 {
+        return 0;
+    }
+
+    let averages = (0..length)
+        .map(|i| series.iter().map(|values| values[i]).sum::<f64>() / series.len() as f64)
+        .collect::<Vec<_>>();
+
+    // the window shrinks near the start, so every average is centered
+    let moving_averages = (0..length.saturating_sub(window))
+        .map(|i| {
+            let half_width = i.min(window);
+            let values = &averages[i - half_width..=i + half_width];
+            values.iter().sum::<f64>() / values.len() as f64
+        })
+        .collect::<Vec<_>>();
+
+    let second_half = &moving_averages[moving_averages.len() / 2..];
+    if second_half.is_empty() {
+        return 0;
+    }
+    let level = second_half.iter().sum::<f64>() / second_half.len() as f64;
+
+    moving_averages
+        .iter()
+        .position(|average| (average - level).abs() <= tolerance * level.abs())
+        .unwrap_or_default()
+}
+
+/// Number of the leading observations to delete by MSER-5, at most a half of the observations.
+pub fn mser5(series: &[f64]) -> usize {
+    const BATCH_SIZE: usize = 5;
+
+    let batches = series
+        .chunks_exact(BATCH_SIZE)
+        .map(|batch| batch.iter().sum::<f64>() / BATCH_SIZE as f64)
+        .collect::<Vec<_>>();
+
+    let mser = |deleted: usize| {
+        let rest = &batches[deleted..];
+        let mean = rest.iter().sum::<f64>() / rest.len() as f64;
+        rest.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (rest.len() as f64).powi(2)
+    };
+
+    let deleted_batches = (0..(batches.len() / 2).max(1).min(batches.len()))
+        .min_by(|a, b| mser(*a).total_cmp(&mser(*b)))
+        .unwrap_or_default();
+
+    deleted_batches * BATCH_SIZE
+}
+
+fn sojourn_times(requests: &[Request]) -> Vec<f64> {
+    requests
+        .iter()
+        .map(|request| f64::from(request.handling_time()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mser5_deletes_the_transient() {
+        // 50 high values followed by a steady alternation of 1 and 2
+        let series = (0..200)
+            .map(|i| match i {
+                0..50 => 10.0,
+                i if i % 2 == 0 => 1.0,
+                _ => 2.0,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(mser5(&series), 50);
+        assert_eq!(mser5(&[1.0; 100]), 0);
+    }
+
+    #[test]
+    fn welch_finds_where_the_average_settles() {
+        // the replications differ by noise that cancels out in their average `1 + 10 e^(-i / 10)`
+        let series = [-0.5, 0.5].map(|noise| {
+            (0..200)
+                .map(|i| 1.0 + 10.0 * (-(i as f64) / 10.0).exp() + noise * (-1f64).powi(i))
+                .collect::<Vec<_>>()
+        });
+
+        // the transient gets below 5% of the level at `10 ln 200 ≈ 53`
+        assert_eq!(welch(&series, 0, 0.05), 53);
+        assert_eq!(welch(&[], 5, 0.05), 0);
+    }
+}
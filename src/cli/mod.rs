@@ -0,0 +1,544 @@
+//! Command-line interface: every subcommand reads a scenario file and prints its results
+//! as a table or as JSON.
+
+mod svg;
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    num::NonZeroUsize,
+    path::PathBuf,
+    thread,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use gl_window_provider::GlWindowProvider;
+use pipeline::{
+    analytic::{Report, TandemNetwork},
+    batch_means::BatchMeans,
+    export,
+    replication::{Estimate, Precision, ReplicationReport},
+    Pipeline, Statistics, Time,
+};
+use rand::rngs::StdRng;
+use serde::Serialize;
+use winit::event_loop::EventLoop;
+
+use crate::{
+    graph_generator::GraphGenerator,
+    renderer::{self, GraphRenderer},
+    scenario::{Arrivals, Scenario, ScenarioError},
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Simulates queueing pipelines described by scenario files"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Format of the results.
+    #[arg(long, value_enum, global = true, default_value_t = Output::Table)]
+    output: Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Table,
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Runs the scenario once and prints its statistics.
+    Simulate {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Working time instead of the scenario's one.
+        #[arg(long)]
+        working_time: Option<f64>,
+        /// Compares the statistics with the theory of the tandem network, it's solvable for
+        /// a line of stations with Poisson arrivals, exponential handling times and either not
+        /// too big buffers or unlimited ones, the latter make a Jackson network.
+        #[arg(long)]
+        theory: bool,
+        /// Writes the trace of the requests that have left, a row per request and station
+        /// passed, as CSV, or as JSON lines if the file ends with `.jsonl`.
+        #[arg(long)]
+        trace: Option<PathBuf>,
+    },
+    /// Runs independent replications and prints the confidence intervals of the metrics.
+    Replicate {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Replications instead of the scenario's ones, the stopping rule is left out.
+        #[arg(long)]
+        replications: Option<usize>,
+    },
+    /// Estimates the mean handling time from a single long run by the method of batch means.
+    BatchMeans {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Working time instead of the scenario's one.
+        #[arg(long)]
+        working_time: Option<f64>,
+        /// Batches to start with, they're merged pairwise while their means are autocorrelated.
+        #[arg(long, default_value_t = 64)]
+        batches: usize,
+        /// The batches aren't merged below this count.
+        #[arg(long, default_value_t = 8)]
+        min_batches: usize,
+    },
+    /// Estimates the replications needed for the precision from pilot replications.
+    Accuracy {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Target half-width as a fraction of the mean.
+        #[arg(long, conflicts_with = "absolute")]
+        relative: Option<f64>,
+        /// Target half-width in the units of the metrics.
+        #[arg(long)]
+        absolute: Option<f64>,
+    },
+    /// Replicates the scenario for every value of a parameter.
+    Sweep {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Path of the parameter, e.g. `stations[0].handling_time.mean`.
+        #[arg(long)]
+        parameter: String,
+        /// Values of the parameter, separated by commas.
+        #[arg(
+            long,
+            value_delimiter = ',',
+            required = true,
+            allow_hyphen_values = true
+        )]
+        values: Vec<String>,
+    },
+    /// Plots the average handling time against the working time in a window or to an SVG file.
+    Plot {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Longest working time of the plot.
+        #[arg(long, default_value_t = 10000.0)]
+        max_time: f32,
+        /// Step of the working time between the points.
+        #[arg(long, default_value_t = 10.0)]
+        step: f32,
+        /// Renders the plot to the SVG file instead of opening a window.
+        #[arg(long)]
+        svg: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+struct CommonArgs {
+    /// Scenario file, `.toml` or `.json`.
+    scenario: PathBuf,
+    /// Threads to run the replications on, all the cores by default.
+    #[arg(long)]
+    threads: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Serialize)]
+struct Simulation<'a> {
+    seed: u64,
+    statistics: &'a Statistics,
+    /// The statistics next to the theory of the tandem or the Jackson network, if it's asked for
+    /// and solvable.
+    theory: Option<Report>,
+}
+
+#[derive(Debug, Serialize)]
+struct Accuracy {
+    pilot_replications: usize,
+    precision: Precision,
+    /// Replications needed for every metric to get within the precision.
+    required_replications: usize,
+    metrics: Vec<MetricAccuracy>,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricAccuracy {
+    metric: String,
+    estimate: Estimate,
+    required_replications: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Sweep<'a> {
+    parameter: &'a str,
+    points: Vec<SweepPoint>,
+}
+
+/// Replications of the scenario with the swept parameter set to the value, or why the scenario
+/// with the value can't be run.
+#[derive(Debug, Serialize)]
+struct SweepPoint {
+    value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<ReplicationReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Cli {
+    pub fn run(self) -> Result<(), Box<dyn Error>> {
+        let output = self.output;
+
+        match self.command {
+            Command::Simulate {
+                common,
+                working_time,
+                theory,
+                trace,
+            } => simulate(&common.load()?, working_time, theory, trace, output),
+            Command::Replicate {
+                common,
+                replications,
+            } => replicate(&common, replications, output),
+            Command::BatchMeans {
+                common,
+                working_time,
+                batches,
+                min_batches,
+            } => {
+                let scenario = common.load()?;
+                let batch_means = BatchMeans {
+                    batches,
+                    min_batches,
+                    level: scenario.run.level,
+                };
+                batch_means_estimate(&scenario, &batch_means, working_time, output)
+            }
+            Command::Accuracy {
+                common,
+                relative,
+                absolute,
+            } => {
+                let precision = relative
+                    .map(Precision::Relative)
+                    .or(absolute.map(Precision::Absolute));
+                accuracy(&common, precision, output)
+            }
+            Command::Sweep {
+                common,
+                parameter,
+                values,
+            } => sweep(&common, &parameter, &values, output),
+            Command::Plot {
+                common,
+                max_time,
+                step,
+                svg,
+            } => plot(&common, max_time, step, svg, output),
+        }
+    }
+}
+
+impl CommonArgs {
+    fn load(&self) -> Result<Scenario, ScenarioError> {
+        Scenario::load(&self.scenario)
+    }
+
+    fn threads(&self) -> NonZeroUsize {
+        self.threads
+            .unwrap_or_else(|| thread::available_parallelism().unwrap_or(NonZeroUsize::MIN))
+    }
+
+    fn pipelines(
+        &self,
+        scenario: &Scenario,
+    ) -> Result<Vec<Pipeline<Arrivals, StdRng>>, ScenarioError> {
+        (0..self.threads().get())
+            .map(|_| scenario.build())
+            .collect()
+    }
+}
+
+fn simulate(
+    scenario: &Scenario,
+    working_time: Option<f64>,
+    theory: bool,
+    trace: Option<PathBuf>,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let mut pipeline = scenario.build::<StdRng>()?;
+    let requests = pipeline.work_during(Time::new(
+        working_time.unwrap_or(scenario.run.working_time),
+    )?)?;
+
+    if let Some(path) = trace {
+        let mut writer = BufWriter::new(File::create(&path)?);
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl") => export::write_json_lines(&requests, &mut writer)?,
+            _ => export::write_csv(&requests, &mut writer)?,
+        }
+        writer.flush()?;
+    }
+
+    let statistics = pipeline.get_statistics();
+    // `None` if the theory isn't asked for, `Some(None)` if the network isn't solvable.
+    let theory = if theory {
+        Some(
+            match scenario.tandem()?.and_then(|tandem| tandem.metrics()) {
+                Some(theory) => Some(Report::tandem(&theory, &statistics)),
+                None => scenario
+                    .jackson()?
+                    .and_then(|jackson| jackson.metrics())
+                    .map(|theory| Report::network(&theory, &statistics)),
+            },
+        )
+    } else {
+        None
+    };
+
+    match output {
+        Output::Table => {
+            println!("seed {}", scenario.seed());
+            println!("{statistics}");
+            match &theory {
+                Some(Some(report)) => println!("{report}"),
+                Some(None) => println!(
+                    "theory n/a: it needs a line of stations with BAS blocking, Poisson arrivals, \
+                     exponential handling times and either unlimited buffers or at most {} states \
+                     of the network",
+                    TandemNetwork::MAX_STATES
+                ),
+                None => {}
+            }
+        }
+        Output::Json => print_json(&Simulation {
+            seed: scenario.seed(),
+            statistics: &statistics,
+            theory: theory.flatten(),
+        })?,
+    }
+
+    Ok(())
+}
+
+fn replicate(
+    common: &CommonArgs,
+    replications: Option<usize>,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let scenario = common.load()?;
+    let mut runner = scenario.runner()?;
+    if let Some(replications) = replications {
+        runner.replications = replications;
+        runner.precision = None;
+    }
+
+    let report = runner.run_parallel(common.threads(), || scenario.build::<StdRng>())?;
+
+    match output {
+        Output::Table => println!("{report}"),
+        Output::Json => print_json(&report)?,
+    }
+
+    Ok(())
+}
+
+fn batch_means_estimate(
+    scenario: &Scenario,
+    batch_means: &BatchMeans,
+    working_time: Option<f64>,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let mut pipeline = scenario.build::<StdRng>()?;
+    let report = batch_means.run(
+        &mut pipeline,
+        Time::new(working_time.unwrap_or(scenario.run.working_time))?,
+    )?;
+
+    match output {
+        Output::Table => print!("{report}"),
+        Output::Json => print_json(&report)?,
+    }
+
+    Ok(())
+}
+
+fn accuracy(
+    common: &CommonArgs,
+    precision: Option<Precision>,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let scenario = common.load()?;
+    let precision = precision
+        .or(scenario.run.precision)
+        .ok_or("the precision is neither given nor in the scenario's run")?;
+
+    let mut runner = scenario.runner()?;
+    runner.precision = None;
+    let pilot = runner.run_parallel(common.threads(), || scenario.build::<StdRng>())?;
+
+    // The estimates are over antithetic pairs, each of them takes two replications.
+    let runs = if pilot.antithetic { 2 } else { 1 };
+    let metrics: Vec<_> = pilot
+        .estimates()
+        .into_iter()
+        .map(|(metric, estimate)| MetricAccuracy {
+            metric,
+            estimate,
+            required_replications: estimate.required_replications(precision, pilot.level) * runs,
+        })
+        .collect();
+    let accuracy = Accuracy {
+        pilot_replications: pilot.replications,
+        precision,
+        required_replications: metrics
+            .iter()
+            .map(|metric| metric.required_replications)
+            .max()
+            .unwrap_or_default(),
+        metrics,
+    };
+
+    match output {
+        Output::Table => {
+            let precision = match precision {
+                Precision::Absolute(half_width) => format!("half-width {half_width}"),
+                Precision::Relative(fraction) => format!("half-width {fraction} of the mean"),
+            };
+            println!(
+                "{} pilot replications, {} required for the {precision}",
+                accuracy.pilot_replications, accuracy.required_replications
+            );
+            println!(
+                "{:<40} {:>12} {:>12} {:>12}",
+                "metric", "mean", "half-width", "required"
+            );
+            for metric in &accuracy.metrics {
+                println!(
+                    "{:<40} {:>12.5} {:>12.5} {:>12}",
+                    metric.metric,
+                    metric.estimate.mean,
+                    metric.estimate.half_width,
+                    metric.required_replications
+                );
+            }
+        }
+        Output::Json => print_json(&accuracy)?,
+    }
+
+    Ok(())
+}
+
+fn sweep(
+    common: &CommonArgs,
+    parameter: &str,
+    values: &[String],
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    let scenario = common.load()?;
+
+    // Every point keeps the seed, so the points are compared with common random numbers.
+    // An invalid value fails its point only.
+    let points = values
+        .iter()
+        .map(|value| {
+            let report = scenario
+                .with_parameter(parameter, value)
+                .and_then(|scenario| {
+                    scenario
+                        .runner()?
+                        .run_parallel(common.threads(), || scenario.build::<StdRng>())
+                });
+
+            match report {
+                Ok(report) => SweepPoint {
+                    value: value.clone(),
+                    report: Some(report),
+                    error: None,
+                },
+                Err(err) => SweepPoint {
+                    value: value.clone(),
+                    report: None,
+                    error: Some(err.to_string()),
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match output {
+        Output::Table => {
+            println!("{parameter}");
+            println!(
+                "{:<12} {:>26} {:>26} {:>26}",
+                "value", "probability of delay", "average handling time", "throughput"
+            );
+            for point in &points {
+                let Some(report) = &point.report else {
+                    let error = point.error.as_deref().unwrap_or_default();
+                    println!("{:<12} error: {error}", point.value);
+                    continue;
+                };
+
+                let interval = |estimate: Estimate| {
+                    format!("{:.5} ± {:.5}", estimate.mean, estimate.half_width)
+                };
+                println!(
+                    "{:<12} {:>26} {:>26} {:>26}",
+                    point.value,
+                    interval(report.probability_of_request_delay),
+                    interval(report.average_handling_time),
+                    interval(report.throughput)
+                );
+            }
+        }
+        Output::Json => print_json(&Sweep { parameter, points })?,
+    }
+
+    Ok(())
+}
+
+fn plot(
+    common: &CommonArgs,
+    max_time: f32,
+    step: f32,
+    svg: Option<PathBuf>,
+    output: Output,
+) -> Result<(), Box<dyn Error>> {
+    if !(step > 0.0 && max_time >= step) {
+        return Err(format!(
+            "the step must be positive and not greater than the max time {max_time}, got {step}"
+        )
+        .into());
+    }
+
+    let scenario = common.load()?;
+    let graph =
+        GraphGenerator::new(common.pipelines(&scenario)?, true, max_time, step).generate()?;
+
+    match output {
+        Output::Table => println!("mean {}, deviation {}", graph.mean, graph.deviation),
+        Output::Json => print_json(&graph)?,
+    }
+
+    match svg {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            svg::write_graph(&graph, &mut writer)?;
+            writer.flush()?;
+        }
+        None => {
+            renderer::set_graph(graph);
+            let event_loop = EventLoop::new();
+            let handler = GlWindowProvider::new(&event_loop).build_handler::<GraphRenderer, ()>();
+            event_loop.run(handler);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)
+}
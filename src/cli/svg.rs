@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+
+use crate::graph_generator::Graph;
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 500.0;
+const MARGIN: f32 = 50.0;
+
+/// Renders the graph like the window does: the points in orange, the mean and the mean
+/// plus and minus the deviation in blue.
+pub(super) fn write_graph<W: Write>(graph: &Graph, writer: &mut W) -> io::Result<()> {
+    let max_y = graph.max_y.max(graph.mean + graph.deviation);
+    let max_y = if max_y > 0.0 { max_y } else { 1.0 };
+    let x = |value: f32| MARGIN + value / graph.max_x * (WIDTH - 2.0 * MARGIN);
+    let y = |value: f32| HEIGHT - MARGIN - value / max_y * (HEIGHT - 2.0 * MARGIN);
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#
+    )?;
+    writeln!(writer, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+    writeln!(
+        writer,
+        r#"<path d="M {left} {top} V {bottom} H {right}" fill="none" stroke="black"/>"#,
+        left = x(0.0),
+        top = y(max_y),
+        bottom = y(0.0),
+        right = x(graph.max_x),
+    )?;
+
+    for level in [
+        graph.mean - graph.deviation,
+        graph.mean,
+        graph.mean + graph.deviation,
+    ] {
+        writeln!(
+            writer,
+            r##"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="#00a6ff"/>"##,
+            x(0.0),
+            x(graph.max_x),
+            y = y(level),
+        )?;
+    }
+
+    let points = graph
+        .points
+        .iter()
+        .map(|(px, py)| format!("{:.2},{:.2}", x(*px), y(*py)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(
+        writer,
+        r##"<polyline points="{points}" fill="none" stroke="#ffa600"/>"##
+    )?;
+
+    writeln!(
+        writer,
+        r#"<text x="{}" y="{}" font-size="12">working time {}</text>"#,
+        x(graph.max_x) - 120.0,
+        y(0.0) + 30.0,
+        graph.max_x
+    )?;
+    writeln!(
+        writer,
+        r#"<text x="{}" y="{}" font-size="12">average handling time {}</text>"#,
+        x(0.0),
+        y(max_y) - 15.0,
+        max_y
+    )?;
+    writeln!(writer, "</svg>")
+}
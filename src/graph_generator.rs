@@ -1,23 +1,31 @@
-use std::{fmt::Debug, ops::ControlFlow};
-
-use pipeline::{Pipeline, Request, Statistics, Time};
-use rand::{prelude::Distribution, Rng};
-
+use std::fmt::Debug;
+
+use pipeline::{
+    arrival::ArrivalProcess,
+    replication::run_parallel,
+    streams::{stream_seed, Stream},
+    Pipeline, PipelineError, Request, Time,
+};
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+/// Runs the pipeline once per point, the point `k` draws from the streams of
+/// [`Stream::Replication`]`(k)`, so the graph doesn't depend on the number of pipelines
+/// the points are spread over.
 #[derive(Debug)]
 pub(crate) struct GraphGenerator<ArrivalDistr, Rand>
 where
-    ArrivalDistr: Distribution<Time> + Debug,
+    ArrivalDistr: ArrivalProcess + Debug,
     Rand: Rng + Debug,
 {
-    pipeline: Pipeline<ArrivalDistr, Rand>,
+    /// Pipelines built from the same config, one per thread.
+    pipelines: Vec<Pipeline<ArrivalDistr, Rand>>,
     max_x: f32,
     x_step: f32,
-    x: f32,
-    max_y: f32,
-    points: Vec<(f32, f32)>,
     increase_working_time: bool,
 }
 
+#[derive(Debug, Serialize)]
 pub(crate) struct Graph {
     pub points: Vec<(f32, f32)>,
     pub mean: f32,
@@ -28,91 +36,71 @@ pub(crate) struct Graph {
 
 impl<ArrivalDistr, Rand> GraphGenerator<ArrivalDistr, Rand>
 where
-    ArrivalDistr: Distribution<Time> + Debug,
-    Rand: Rng + Debug,
+    ArrivalDistr: ArrivalProcess + Debug + Send,
+    Rand: Rng + SeedableRng + Debug + Send,
 {
     pub fn new(
-        pipeline: Pipeline<ArrivalDistr, Rand>,
+        pipelines: Vec<Pipeline<ArrivalDistr, Rand>>,
         increase_working_time: bool,
         max_x: f32,
         x_step: f32,
     ) -> Self {
         Self {
             increase_working_time,
-            pipeline,
+            pipelines,
             max_x,
             x_step,
-            x: 0.0,
-            max_y: 0.0,
-            points: Vec::with_capacity((max_x / x_step) as usize),
         }
     }
 
-    pub fn generate(&mut self) -> Graph {
-        self.pipeline.reset();
-        self.x = 0.0;
-        self.max_y = 0.0;
-
-        loop {
-            match self.next() {
-                ControlFlow::Break(()) => break,
-                ControlFlow::Continue(()) => continue,
-            }
+    pub fn generate(&mut self) -> Result<Graph, PipelineError> {
+        let seed = self.pipelines[0].seed();
+        let points_count = (self.max_x / self.x_step) as usize;
+
+        let mut points = vec![(0.0, 0.0)];
+        let results = run_parallel(&mut self.pipelines, 1..points_count + 1, |pipeline, k| {
+            let x = k as f32 * self.x_step;
+            pipeline.reseed(stream_seed(seed, Stream::Replication(k)), false);
+            pipeline.reset();
+            let working_time = if self.increase_working_time {
+                x
+            } else {
+                self.max_x
+            };
+            let requests = pipeline.work_during(Time::new(working_time as f64)?)?;
+
+            Ok::<_, PipelineError>((x, Self::calc_average_time(&requests)))
+        });
+        for point in results {
+            points.push(point?);
         }
 
-        let (mean, deviation) = self.calc_mean_and_deviation(&self.points);
+        let (mean, deviation) = Self::calc_mean_and_deviation(&points);
+        let max_y = points.iter().map(|(_, y)| *y).fold(0.0, f32::max);
 
-        Graph {
+        Ok(Graph {
             mean,
             deviation,
-            points: self.points.drain(..).collect(),
+            points,
             max_x: self.max_x,
-            max_y: self.max_y,
-        }
-    }
-
-    fn next(&mut self) -> ControlFlow<(), ()> {
-        if self.x > self.max_x {
-            return ControlFlow::Break(());
-        }
-
-        if self.x == 0.0 {
-            self.points.push((0.0, 0.0));
-            self.x = self.x_step;
-            return ControlFlow::Continue(());
-        }
-
-        self.pipeline.reset();
-        let requests = self.pipeline.work_during(if self.increase_working_time {
-            Time::from(self.x)
-        } else {
-            Time::from(self.max_x)
-        });
-
-        let average_time = self.calc_average_time(&requests);
-        if average_time > self.max_y {
-            self.max_y = average_time;
-        }
-
-        self.points.push((f32::from(self.x), average_time));
-        self.x += self.x_step;
-
-        ControlFlow::Continue(())
+            max_y,
+        })
     }
 
-    fn calc_average_time(&self, requests: &[Request]) -> f32 {
-        if requests.len() == 0 {
+    fn calc_average_time(requests: &[Request]) -> f32 {
+        if requests.is_empty() {
             return 0.0;
         }
 
-        requests
-            .into_iter()
-            .map(|req| f32::from(req.leaving_time - req.arrival_time))
-            .sum::<f32>()
-            / requests.len() as f32
+        let total_time = requests
+            .iter()
+            .map(|req| f64::from(req.handling_time()))
+            .sum::<f64>();
+
+        (total_time / requests.len() as f64) as f32
     }
 
-    fn calc_mean_and_deviation(&self, points: &[(f32, f32)]) -> (f32, f32) {
+    fn calc_mean_and_deviation(points: &[(f32, f32)]) -> (f32, f32) {
         let sum = points.iter().map(|(_, y)| *y).sum::<f32>();
         let mean = sum / points.len() as f32;
 
@@ -123,46 +111,3 @@ where
         (mean, deviation)
     }
 }
-
-pub(crate) fn calc_average_stats<ArrivalDistr, Rand>(
-    graph_generator: &mut GraphGenerator<ArrivalDistr, Rand>,
-    iters_count: usize,
-) -> Statistics
-where
-    ArrivalDistr: Distribution<Time> + Debug,
-    Rand: Rng + Debug,
-{
-    let mut statistics = Statistics::default();
-
-    for _ in 0..iters_count {
-        let Graph { .. } = graph_generator.generate();
-        statistics += graph_generator.pipeline.get_statistics();
-    }
-
-    statistics /= iters_count as f32;
-
-    statistics
-}
-
-pub(crate) fn achive_calc_accuracy<ArrivalDistr, Rand>(
-    graph_generator: &mut GraphGenerator<ArrivalDistr, Rand>,
-    mut n: f32,
-    precision: f32,
-    quantile: f32,
-) -> f32
-where
-    ArrivalDistr: Distribution<Time> + Debug,
-    Rand: Rng + Debug,
-{
-    loop {
-        let Graph { deviation, .. } = graph_generator.generate();
-        let n_asterisk = ((deviation.powi(2) / precision) * quantile).powi(2);
-
-        if n_asterisk > n {
-            n = n_asterisk;
-            continue;
-        }
-
-        break n_asterisk;
-    }
-}
@@ -16,9 +16,7 @@ pub struct AttribPointer {
 
 pub enum Size {
     One = 1isize,
-    Two = 2,
     Three = 3,
-    Four = 4,
 }
 
 pub struct VerticesArray {
@@ -41,7 +39,7 @@ impl VerticesArray {
 
             gl.BufferData(
                 gl::ARRAY_BUFFER,
-                (data.len() * std::mem::size_of::<T>()) as gl::types::GLsizeiptr,
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr,
                 data.as_ptr() as *const _,
                 gl::DYNAMIC_DRAW,
             );
@@ -77,22 +75,6 @@ impl VerticesArray {
         }
     }
 
-    pub fn set_attrib_int_pointer(&self, location: AttribLocation, value: AttribPointer) {
-        unsafe {
-            self.gl.BindVertexArray(self.vao);
-            self.gl.BindBuffer(gl::ARRAY_BUFFER, self.vbo);
-
-            self.gl.VertexAttribIPointer(
-                location.0,
-                value.size as i32,
-                value.ty,
-                value.stride as i32,
-                value.offset as *const _,
-            );
-            self.gl.EnableVertexAttribArray(location.0);
-        }
-    }
-
     pub fn use_array(&self) {
         unsafe {
             self.gl.BindVertexArray(self.vao);
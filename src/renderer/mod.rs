@@ -3,23 +3,27 @@ mod error;
 mod shader_kind;
 mod shader_program;
 
-use std::ffi::CString;
+use std::{ffi::CString, sync::OnceLock};
 
 use gl_window_provider::Renderer;
-use pipeline::{PipelineParams, Time};
-use rand::{prelude::Distribution, thread_rng};
 
-use crate::{
-    exp_distr::ExpDistr,
-    graph_generator::{Graph, GraphGenerator},
-    service::ServiceParams,
-};
+use crate::graph_generator::Graph;
 
 use self::{
     array::VerticesArray,
     shader_program::{ShaderProgram, ShaderProgramBuilder},
 };
 
+/// Graph to draw, [`Renderer::new`] has no way to get it but a static.
+static GRAPH: OnceLock<Graph> = OnceLock::new();
+
+/// Sets the graph to draw, it has to be set before the window is opened.
+pub(crate) fn set_graph(graph: Graph) {
+    if GRAPH.set(graph).is_err() {
+        panic!("the graph is set once");
+    }
+}
+
 pub(crate) struct GraphRenderer {
     gl: gl::Gl,
     graph_points_array: VerticesArray,
@@ -37,34 +41,19 @@ impl Renderer for GraphRenderer {
             gl_display.get_proc_address(symbol.as_c_str()).cast()
         });
 
-        let pipeline = PipelineParams {
-            arrival_distr: ExpDistr::new(0.4),
-            rand_gen: thread_rng(),
-        }
-        .build(vec![
-            Box::new(ServiceParams {
-                buffer_size: 4,
-                handling_time_distribution: ExpDistr::new(1.25),
-            }),
-            Box::new(ServiceParams {
-                buffer_size: 2,
-                handling_time_distribution: ExpDistr::new(0.5),
-            }),
-        ]);
-
-        let max_x = 10000.0;
         let Graph {
             max_x,
             max_y,
             mean,
             deviation,
             points,
-        } = GraphGenerator::new(pipeline, true, max_x, 10.0).generate();
-
-        dbg!(mean, deviation);
+        } = GRAPH
+            .get()
+            .expect("the graph is set before the window is opened");
+        let (max_x, max_y, mean, deviation) = (*max_x, *max_y, *mean, *deviation);
 
         let graph_points = points
-            .into_iter()
+            .iter()
             .map(|(x, y)| (x / max_x, y / max_y, 1.0f32, 0.65f32, 0.0f32))
             .collect::<Vec<_>>();
         let graph_points_array = VerticesArray::new(gl.clone(), &graph_points);
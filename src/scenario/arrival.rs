@@ -0,0 +1,355 @@
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+use pipeline::{
+    arrival::{
+        Arrival, ArrivalProcess, BatchArrivals, MmppArrivals, MultiClassArrivals, NhppArrivals,
+        PiecewiseConstantRate, RequestClass, TraceArrivals,
+    },
+    distr::Moments,
+    PipelineError, Time,
+};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+
+use super::{Distr, DistributionConfig, ScenarioError};
+
+/// Types of the arrival processes that aren't a renewal process of a distribution.
+const PROCESSES: [&str; 5] = ["multi_class", "trace", "nhpp", "mmpp", "batch"];
+
+/// Arrivals of a scenario: either a distribution of the times between the arrivals, e.g.
+/// `{ type = "exponential", mean = 2.5 }`, or a process tagged by its `type`, e.g.
+/// `{ type = "mmpp", rates = [1.0, 5.0], transitions = [[0.0, 0.1], [0.5, 0.0]] }`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ArrivalConfig {
+    Renewal(DistributionConfig),
+    Process(ProcessConfig),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ProcessConfig {
+    /// Independent arrivals of several classes, the class of a request is its index.
+    MultiClass { classes: Vec<ClassConfig> },
+    /// Arrivals replayed from a `.csv` or a `.jsonl` file, relative to the working directory.
+    Trace { path: PathBuf },
+    /// Poisson arrivals with a piecewise constant rate, the pieces as `[start, rate]` pairs.
+    Nhpp {
+        pieces: Vec<(f64, f64)>,
+        period: Option<f64>,
+    },
+    /// Markov-modulated Poisson arrivals starting in phase `0`.
+    Mmpp {
+        rates: Vec<f64>,
+        transitions: Vec<Vec<f64>>,
+    },
+    /// Every arrival of `arrivals` brings a batch of requests.
+    Batch {
+        arrivals: Box<ArrivalConfig>,
+        size: BatchSizeConfig,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClassConfig {
+    pub interarrival_time: DistributionConfig,
+    #[serde(default)]
+    pub priority: usize,
+}
+
+/// Distribution of the number of requests in a batch, at least one on average.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BatchSizeConfig {
+    Fixed {
+        size: usize,
+    },
+    Uniform {
+        min: usize,
+        max: usize,
+    },
+    /// Geometric on `1, 2, ...`.
+    Geometric {
+        mean: f64,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum BatchSize {
+    Fixed(usize),
+    Uniform(Uniform<usize>),
+    /// Geometric with the success probability.
+    Geometric(f64),
+}
+
+/// Any arrival process of a scenario.
+#[derive(Debug)]
+pub enum Arrivals {
+    Renewal(Distr),
+    MultiClass(MultiClassArrivals<Distr>),
+    Trace(TraceArrivals),
+    Nhpp(NhppArrivals<PiecewiseConstantRate>),
+    Mmpp(MmppArrivals),
+    Batch(Box<BatchArrivals<Arrivals, BatchSize>>),
+}
+
+impl<'de> Deserialize<'de> for ArrivalConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let process = value
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(|kind| PROCESSES.contains(&kind));
+
+        if process {
+            ProcessConfig::deserialize(value)
+                .map(Self::Process)
+                .map_err(D::Error::custom)
+        } else {
+            DistributionConfig::deserialize(value)
+                .map(Self::Renewal)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+impl ArrivalConfig {
+    /// Builds the arrivals, `field` is the path of the config used in the errors.
+    pub fn build(&self, field: &str) -> Result<Arrivals, ScenarioError> {
+        let arrivals = match self {
+            Self::Renewal(distr) => Arrivals::Renewal(
+                distr
+                    .build()
+                    .map_err(|err| ScenarioError::invalid(field, err))?,
+            ),
+            Self::Process(process) => process.build(field)?,
+        };
+        arrivals
+            .validate()
+            .map_err(|err| ScenarioError::invalid(field, err))?;
+
+        Ok(arrivals)
+    }
+
+    /// Long-run rate of the requests, `None` if it isn't known in advance, e.g. of a trace.
+    pub fn rate(&self) -> Option<f64> {
+        match self {
+            Self::Renewal(distr) => Some(1.0 / distr.build().ok()?.mean()),
+            Self::Process(ProcessConfig::MultiClass { classes }) => classes
+                .iter()
+                .map(|class| Some(1.0 / class.interarrival_time.build().ok()?.mean()))
+                .sum(),
+            Self::Process(ProcessConfig::Batch { arrivals, size }) => {
+                Some(arrivals.rate()? * size.mean())
+            }
+            Self::Process(_) => None,
+        }
+    }
+}
+
+impl ProcessConfig {
+    fn build(&self, field: &str) -> Result<Arrivals, ScenarioError> {
+        Ok(match self {
+            Self::MultiClass { classes } => {
+                let classes = classes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, class)| {
+                        let field = format!("{field}.classes[{i}].interarrival_time");
+                        let arrival_distr = class
+                            .interarrival_time
+                            .build()
+                            .map_err(|err| ScenarioError::invalid(&field, err))?;
+                        arrival_distr
+                            .validate()
+                            .map_err(|err| ScenarioError::invalid(&field, err))?;
+
+                        Ok(RequestClass {
+                            arrival_distr,
+                            priority: class.priority,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, ScenarioError>>()?;
+                if classes.is_empty() {
+                    return Err(ScenarioError::Invalid {
+                        field: format!("{field}.classes"),
+                        message: "at least one class is required".to_owned(),
+                    });
+                }
+
+                Arrivals::MultiClass(MultiClassArrivals::new(classes))
+            }
+            Self::Trace { path } => {
+                let invalid = |message: String| ScenarioError::Invalid {
+                    field: format!("{field}.path"),
+                    message,
+                };
+                let reader =
+                    BufReader::new(File::open(path).map_err(|err| {
+                        invalid(format!("failed to read {}: {err}", path.display()))
+                    })?);
+
+                let trace = match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("csv") => TraceArrivals::read_csv(reader),
+                    Some("jsonl") => TraceArrivals::read_json_lines(reader),
+                    _ => {
+                        return Err(invalid(format!(
+                            "unsupported trace format of {}, expected .csv or .jsonl",
+                            path.display()
+                        )))
+                    }
+                };
+
+                Arrivals::Trace(trace.map_err(|err| invalid(err.to_string()))?)
+            }
+            Self::Nhpp { pieces, period } => {
+                let pieces = pieces
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (start, rate))| {
+                        let field = format!("{field}.pieces[{i}]");
+                        check_rate(&field, *rate)?;
+                        let start = Time::new(*start).map_err(|err| ScenarioError::Invalid {
+                            field,
+                            message: err.to_string(),
+                        })?;
+
+                        Ok((start, *rate))
+                    })
+                    .collect::<Result<Vec<_>, ScenarioError>>()?;
+                let rate = match period {
+                    Some(period) => {
+                        let period = Time::new(*period).map_err(|err| ScenarioError::Invalid {
+                            field: format!("{field}.period"),
+                            message: err.to_string(),
+                        })?;
+                        PiecewiseConstantRate::periodic(pieces, period)
+                    }
+                    None => PiecewiseConstantRate::new(pieces),
+                };
+
+                Arrivals::Nhpp(NhppArrivals::new(
+                    rate.map_err(|err| ScenarioError::invalid(field, err))?,
+                ))
+            }
+            Self::Mmpp { rates, transitions } => {
+                for (i, rate) in rates.iter().enumerate() {
+                    check_rate(&format!("{field}.rates[{i}]"), *rate)?;
+                }
+                for (i, row) in transitions.iter().enumerate() {
+                    for (j, rate) in row.iter().enumerate() {
+                        check_rate(&format!("{field}.transitions[{i}][{j}]"), *rate)?;
+                    }
+                }
+
+                Arrivals::Mmpp(
+                    MmppArrivals::new(rates.clone(), transitions.clone())
+                        .map_err(|err| ScenarioError::invalid(field, err))?,
+                )
+            }
+            Self::Batch { arrivals, size } => Arrivals::Batch(Box::new(BatchArrivals::new(
+                arrivals.build(&format!("{field}.arrivals"))?,
+                size.build(&format!("{field}.size"))?,
+            ))),
+        })
+    }
+}
+
+impl BatchSizeConfig {
+    fn build(&self, field: &str) -> Result<BatchSize, ScenarioError> {
+        let invalid = |parameter: &str, message: String| ScenarioError::Invalid {
+            field: format!("{field}.{parameter}"),
+            message,
+        };
+
+        match *self {
+            Self::Fixed { size: 0 } => Err(invalid(
+                "size",
+                "a batch needs at least one request".to_owned(),
+            )),
+            Self::Fixed { size } => Ok(BatchSize::Fixed(size)),
+            Self::Uniform { max: 0, .. } => Err(invalid(
+                "max",
+                "a batch needs at least one request".to_owned(),
+            )),
+            Self::Uniform { min, max } if min > max => Err(invalid(
+                "max",
+                format!("must not be less than the min {min}, got {max}"),
+            )),
+            Self::Uniform { min, max } => Ok(BatchSize::Uniform(Uniform::new_inclusive(min, max))),
+            Self::Geometric { mean } if !(mean.is_finite() && mean >= 1.0) => {
+                Err(invalid("mean", format!("must be at least 1, got {mean}")))
+            }
+            Self::Geometric { mean } => Ok(BatchSize::Geometric(1.0 / mean)),
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        match *self {
+            Self::Fixed { size } => size as f64,
+            Self::Uniform { min, max } => (min + max) as f64 / 2.0,
+            Self::Geometric { mean } => mean,
+        }
+    }
+}
+
+impl Distribution<usize> for BatchSize {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        match self {
+            Self::Fixed(size) => *size,
+            Self::Uniform(distr) => distr.sample(rng),
+            Self::Geometric(success) => {
+                // Inversion of the number of trials up to the first success, `1 - u` is never zero.
+                let u = 1.0 - rng.gen::<f64>();
+                1 + (u.ln() / (1.0 - success).ln()).floor() as usize
+            }
+        }
+    }
+}
+
+impl ArrivalProcess for Arrivals {
+    fn next_arrival<R: Rng + ?Sized>(&mut self, now: Time, rng: &mut R) -> Option<Arrival> {
+        match self {
+            Self::Renewal(arrivals) => arrivals.next_arrival(now, rng),
+            Self::MultiClass(arrivals) => arrivals.next_arrival(now, rng),
+            Self::Trace(arrivals) => arrivals.next_arrival(now, rng),
+            Self::Nhpp(arrivals) => arrivals.next_arrival(now, rng),
+            Self::Mmpp(arrivals) => arrivals.next_arrival(now, rng),
+            Self::Batch(arrivals) => arrivals.next_arrival(now, rng),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Self::Renewal(arrivals) => arrivals.reset(),
+            Self::MultiClass(arrivals) => arrivals.reset(),
+            Self::Trace(arrivals) => arrivals.reset(),
+            Self::Nhpp(arrivals) => arrivals.reset(),
+            Self::Mmpp(arrivals) => arrivals.reset(),
+            Self::Batch(arrivals) => arrivals.reset(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), PipelineError> {
+        match self {
+            Self::Renewal(arrivals) => arrivals.validate(),
+            Self::MultiClass(arrivals) => arrivals.validate(),
+            Self::Trace(arrivals) => arrivals.validate(),
+            Self::Nhpp(arrivals) => arrivals.validate(),
+            Self::Mmpp(arrivals) => arrivals.validate(),
+            Self::Batch(arrivals) => arrivals.validate(),
+        }
+    }
+}
+
+fn check_rate(field: &str, rate: f64) -> Result<(), ScenarioError> {
+    if rate.is_finite() && rate >= 0.0 {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid {
+            field: field.to_owned(),
+            message: format!("a rate must be non-negative, got {rate}"),
+        })
+    }
+}
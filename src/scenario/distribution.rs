@@ -0,0 +1,273 @@
+use pipeline::{
+    distr::{
+        DeterministicDistr, EmpiricalDistr, ErlangDistr, ExpDistr, GammaDistr, HyperExpDistr,
+        LogNormalDistr, Moments, ParetoDistr, TruncNormalDistr, UniformDistr, WeibullDistr,
+    },
+    PipelineError, Time,
+};
+use rand::{prelude::Distribution, Rng};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+
+use super::ScenarioError;
+use crate::service::handling_time::PerClass;
+
+/// Distribution of a scenario, tagged by its `type`, e.g. `{ type = "exponential", mean = 2.5 }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DistributionConfig {
+    Exponential {
+        mean: f64,
+    },
+    Deterministic {
+        value: f64,
+    },
+    Erlang {
+        k: usize,
+        mean: f64,
+    },
+    Gamma {
+        shape: f64,
+        scale: f64,
+    },
+    /// Phases as `[probability, mean]` pairs.
+    HyperExponential {
+        phases: Vec<(f64, f64)>,
+    },
+    LogNormal {
+        mu: f64,
+        sigma: f64,
+    },
+    Pareto {
+        shape: f64,
+        scale: f64,
+    },
+    TruncatedNormal {
+        mean: f64,
+        deviation: f64,
+    },
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    Weibull {
+        shape: f64,
+        scale: f64,
+    },
+    /// Histogram bins as `[from, to, weight]`.
+    Empirical {
+        bins: Vec<(f64, f64, f64)>,
+    },
+}
+
+/// Handling time of a station: a distribution, or a list of them by the request class,
+/// e.g. `[{ type = "exponential", mean = 1.0 }, { type = "deterministic", value = 2.0 }]`,
+/// the classes past the end of the list use its last distribution.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum HandlingTimeConfig {
+    Single(DistributionConfig),
+    PerClass(Vec<DistributionConfig>),
+}
+
+/// Any distribution of the pipeline, so the stations of a scenario can differ in theirs.
+#[derive(Debug, Clone)]
+pub enum Distr {
+    Exponential(ExpDistr),
+    Deterministic(DeterministicDistr),
+    Erlang(ErlangDistr),
+    Gamma(GammaDistr),
+    HyperExponential(HyperExpDistr),
+    LogNormal(LogNormalDistr),
+    Pareto(ParetoDistr),
+    TruncatedNormal(TruncNormalDistr),
+    Uniform(UniformDistr),
+    Weibull(WeibullDistr),
+    Empirical(EmpiricalDistr),
+}
+
+impl DistributionConfig {
+    pub fn build(&self) -> Result<Distr, PipelineError> {
+        Ok(match self {
+            Self::Exponential { mean } => Distr::Exponential(ExpDistr::new(*mean)?),
+            Self::Deterministic { value } => Distr::Deterministic(DeterministicDistr::new(*value)?),
+            Self::Erlang { k, mean } => Distr::Erlang(ErlangDistr::new(*k, *mean)?),
+            Self::Gamma { shape, scale } => Distr::Gamma(GammaDistr::new(*shape, *scale)?),
+            Self::HyperExponential { phases } => {
+                Distr::HyperExponential(HyperExpDistr::new(phases.clone())?)
+            }
+            Self::LogNormal { mu, sigma } => Distr::LogNormal(LogNormalDistr::new(*mu, *sigma)?),
+            Self::Pareto { shape, scale } => Distr::Pareto(ParetoDistr::new(*shape, *scale)?),
+            Self::TruncatedNormal { mean, deviation } => {
+                Distr::TruncatedNormal(TruncNormalDistr::new(*mean, *deviation)?)
+            }
+            Self::Uniform { min, max } => Distr::Uniform(UniformDistr::new(*min, *max)?),
+            Self::Weibull { shape, scale } => Distr::Weibull(WeibullDistr::new(*shape, *scale)?),
+            Self::Empirical { bins } => {
+                Distr::Empirical(EmpiricalDistr::from_histogram(bins.clone())?)
+            }
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for HandlingTimeConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        if value.is_array() {
+            Vec::deserialize(value)
+                .map(Self::PerClass)
+                .map_err(D::Error::custom)
+        } else {
+            DistributionConfig::deserialize(value)
+                .map(Self::Single)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+impl HandlingTimeConfig {
+    /// Builds the distributions, `field` is the path of the config used in the errors.
+    pub fn build(&self, field: &str) -> Result<PerClass<Distr>, ScenarioError> {
+        let distributions = match self {
+            Self::Single(distr) => vec![distr
+                .build()
+                .map_err(|err| ScenarioError::invalid(field, err))?],
+            Self::PerClass(distributions) => distributions
+                .iter()
+                .enumerate()
+                .map(|(i, distr)| {
+                    distr
+                        .build()
+                        .map_err(|err| ScenarioError::invalid(&format!("{field}[{i}]"), err))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        PerClass::new(distributions).map_err(|_| ScenarioError::Invalid {
+            field: field.to_owned(),
+            message: "at least one distribution is required".to_owned(),
+        })
+    }
+}
+
+impl Distribution<Time> for Distr {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Time {
+        match self {
+            Self::Exponential(distr) => distr.sample(rng),
+            Self::Deterministic(distr) => distr.sample(rng),
+            Self::Erlang(distr) => distr.sample(rng),
+            Self::Gamma(distr) => distr.sample(rng),
+            Self::HyperExponential(distr) => distr.sample(rng),
+            Self::LogNormal(distr) => distr.sample(rng),
+            Self::Pareto(distr) => distr.sample(rng),
+            Self::TruncatedNormal(distr) => distr.sample(rng),
+            Self::Uniform(distr) => distr.sample(rng),
+            Self::Weibull(distr) => distr.sample(rng),
+            Self::Empirical(distr) => distr.sample(rng),
+        }
+    }
+}
+
+impl Moments for Distr {
+    fn mean(&self) -> f64 {
+        self.moments().mean()
+    }
+
+    fn variance(&self) -> f64 {
+        self.moments().variance()
+    }
+}
+
+impl Distr {
+    fn moments(&self) -> &dyn Moments {
+        match self {
+            Self::Exponential(distr) => distr,
+            Self::Deterministic(distr) => distr,
+            Self::Erlang(distr) => distr,
+            Self::Gamma(distr) => distr,
+            Self::HyperExponential(distr) => distr,
+            Self::LogNormal(distr) => distr,
+            Self::Pareto(distr) => distr,
+            Self::TruncatedNormal(distr) => distr,
+            Self::Uniform(distr) => distr,
+            Self::Weibull(distr) => distr,
+            Self::Empirical(distr) => distr,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Compares the sample mean and variance of many draws with the closed forms, within
+    /// `tolerance` of them relatively.
+    fn assert_moments(config: DistributionConfig, tolerance: f64) {
+        const COUNT: usize = 100_000;
+
+        let distr = config.build().unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let samples = (0..COUNT)
+            .map(|_| f64::from(distr.sample(&mut rng)))
+            .collect::<Vec<_>>();
+        let mean = samples.iter().sum::<f64>() / COUNT as f64;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample - mean).powi(2))
+            .sum::<f64>()
+            / (COUNT - 1) as f64;
+
+        assert!(
+            (mean - distr.mean()).abs() <= tolerance * distr.mean(),
+            "{config:?}: sample mean {mean}, expected {}",
+            distr.mean()
+        );
+        assert!(
+            (variance - distr.variance()).abs() <= tolerance * distr.variance(),
+            "{config:?}: sample variance {variance}, expected {}",
+            distr.variance()
+        );
+    }
+
+    #[test]
+    fn sample_moments_match_the_closed_forms() {
+        let configs = [
+            DistributionConfig::Exponential { mean: 2.0 },
+            DistributionConfig::Deterministic { value: 1.5 },
+            DistributionConfig::Erlang { k: 3, mean: 1.2 },
+            DistributionConfig::Gamma {
+                shape: 0.7,
+                scale: 1.5,
+            },
+            DistributionConfig::HyperExponential {
+                phases: vec![(0.8, 0.5), (0.2, 3.0)],
+            },
+            DistributionConfig::LogNormal {
+                mu: 0.0,
+                sigma: 0.5,
+            },
+            DistributionConfig::Pareto {
+                shape: 6.0,
+                scale: 1.0,
+            },
+            // the truncation at zero cuts off a sixth of the normal distribution
+            DistributionConfig::TruncatedNormal {
+                mean: 0.5,
+                deviation: 0.5,
+            },
+            DistributionConfig::Uniform { min: 1.0, max: 3.0 },
+            DistributionConfig::Weibull {
+                shape: 1.5,
+                scale: 2.0,
+            },
+            DistributionConfig::Empirical {
+                bins: vec![(0.0, 1.0, 1.0), (1.0, 2.0, 3.0), (4.0, 5.0, 1.0)],
+            },
+        ];
+
+        for config in configs {
+            assert_moments(config, 0.05);
+        }
+    }
+}
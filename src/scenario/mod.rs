@@ -0,0 +1,542 @@
+//! Scenarios read from TOML or JSON files: the arrivals, the network of stations they visit,
+//! and how long and how many times to run it.
+
+mod arrival;
+mod distribution;
+mod network;
+
+pub use self::arrival::{ArrivalConfig, Arrivals};
+pub use self::distribution::{Distr, DistributionConfig, HandlingTimeConfig};
+pub use self::network::{BlockingConfig, ForkConfig, JoinConfig, RouterConfig, TargetConfig};
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use pipeline::{
+    analytic::{JacksonNetwork, Station, TandemNetwork},
+    node::IntoPipelineNode,
+    replication::{Precision, ReplicationRunner},
+    warm_up::WarmUpDetection,
+    Pipeline, PipelineError, PipelineParams, Time,
+};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::service::{
+    discipline::{Fifo, Lifo, Priority, QueueDiscipline, Siro, Sjf},
+    handling_time::PerClass,
+    Preemption, ServiceParams,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub arrival: ArrivalConfig,
+    /// Stations, a station passes the requests to the next one unless it says otherwise.
+    pub stations: Vec<StationConfig>,
+    /// Node the arriving requests go to, the first station by default.
+    pub entry: Option<TargetConfig>,
+    #[serde(default)]
+    pub routers: Vec<RouterConfig>,
+    #[serde(default)]
+    pub forks: Vec<ForkConfig>,
+    #[serde(default)]
+    pub joins: Vec<JoinConfig>,
+    #[serde(default)]
+    pub run: RunConfig,
+    /// Seed of the random streams, the loader picks a random one if it's missing.
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StationConfig {
+    /// The buffer is unlimited if it's missing.
+    pub buffer_size: Option<usize>,
+    #[serde(default = "one")]
+    pub servers: usize,
+    pub handling_time: HandlingTimeConfig,
+    #[serde(default)]
+    pub discipline: Discipline,
+    #[serde(default)]
+    pub preemption: Preemption,
+    /// Node the handled requests go to, the next station or the exit after the last one.
+    pub next: Option<TargetConfig>,
+    #[serde(default)]
+    pub blocking: BlockingConfig,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Discipline {
+    #[default]
+    Fifo,
+    Lifo,
+    Siro,
+    Sjf,
+    Priority,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    pub working_time: f64,
+    /// Part of the working time left out of the statistics.
+    pub warm_up: Option<WarmUpConfig>,
+    pub replications: usize,
+    pub max_replications: usize,
+    /// Confidence level of the intervals.
+    pub level: f64,
+    pub precision: Option<Precision>,
+    pub antithetic: bool,
+    /// Corrects the average handling time with the observed arrival rate.
+    pub control_variate: bool,
+    /// Warm-up found by the detection when the scenario is loaded.
+    #[serde(skip)]
+    detected_warm_up: Option<f64>,
+}
+
+/// Warm-up of the runs, either fixed, e.g. `50.0`, or detected from pilot runs of the working
+/// time, e.g. `"mser5"` or `{ welch = { replications = 5, window = 10, tolerance = 0.05 } }`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WarmUpConfig {
+    Fixed(f64),
+    Detected(WarmUpDetectionConfig),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum WarmUpDetectionConfig {
+    Welch {
+        replications: usize,
+        window: usize,
+        tolerance: f64,
+    },
+    Mser5,
+}
+
+/// Error of loading or running a scenario.
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The extension is neither `toml` nor `json`.
+    UnsupportedFormat(PathBuf),
+    /// The text isn't TOML or JSON of a scenario, the message tells where.
+    Parse(String),
+    /// The value of the field is out of its domain, the field is given by its path,
+    /// e.g. `stations[1].handling_time.mean`.
+    Invalid {
+        field: String,
+        message: String,
+    },
+    /// The links of the network don't make a valid network, e.g. they make a cycle.
+    Network(String),
+    /// The simulation failed, e.g. an event got past the largest time.
+    Run(PipelineError),
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            working_time: 750.0,
+            warm_up: None,
+            replications: 10,
+            max_replications: 1000,
+            level: 0.95,
+            precision: None,
+            antithetic: false,
+            control_variate: false,
+            detected_warm_up: None,
+        }
+    }
+}
+
+impl Scenario {
+    /// Reads the scenario from a `.toml` or `.json` file and validates it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| ScenarioError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(ScenarioError::UnsupportedFormat(path.to_owned())),
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, ScenarioError> {
+        let scenario: Self =
+            toml::from_str(text).map_err(|err| ScenarioError::Parse(err.to_string()))?;
+        scenario.validated()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ScenarioError> {
+        let scenario: Self =
+            serde_json::from_str(text).map_err(|err| ScenarioError::Parse(err.to_string()))?;
+        scenario.validated()
+    }
+
+    /// Checks every field and picks the seed if it's missing, so every pipeline built
+    /// from the scenario draws the same random numbers.
+    fn validated(mut self) -> Result<Self, ScenarioError> {
+        self.seed.get_or_insert_with(|| thread_rng().gen());
+        self.run.validate()?;
+        let mut pipeline = self.build::<StdRng>()?;
+        let runner = self.runner()?;
+
+        if let Some(WarmUpConfig::Detected(detection)) = self.run.warm_up {
+            let warm_up = detection
+                .detection()
+                .detect(&mut pipeline, runner.working_time)?;
+            self.run.detected_warm_up = Some(f64::from(warm_up));
+        }
+
+        Ok(self)
+    }
+
+    /// The scenario with the field at `path`, e.g. `stations[1].handling_time.mean`, set to
+    /// `value`. The value is parsed as JSON, a value that isn't JSON is taken as a string.
+    pub fn with_parameter(&self, path: &str, value: &str) -> Result<Self, ScenarioError> {
+        let value = serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+        let mut scenario = serde_json::to_value(self).expect("a scenario is serializable");
+
+        let field = path
+            .split('.')
+            .flat_map(|key| key.split(['[', ']']))
+            .filter(|key| !key.is_empty())
+            .try_fold(&mut scenario, |field, key| match field {
+                serde_json::Value::Object(fields) => fields.get_mut(key),
+                serde_json::Value::Array(items) => items.get_mut(key.parse::<usize>().ok()?),
+                _ => None,
+            })
+            .ok_or_else(|| ScenarioError::Invalid {
+                field: path.to_owned(),
+                message: "no such field in the scenario".to_owned(),
+            })?;
+        *field = value;
+
+        let scenario: Self =
+            serde_json::from_value(scenario).map_err(|err| ScenarioError::Invalid {
+                field: path.to_owned(),
+                message: err.to_string(),
+            })?;
+        scenario.validated()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or_default()
+    }
+
+    pub fn arrivals(&self) -> Result<Arrivals, ScenarioError> {
+        self.arrival.build("arrival")
+    }
+
+    pub fn services(&self) -> Result<Vec<ServiceParams<PerClass<Distr>>>, ScenarioError> {
+        self.stations
+            .iter()
+            .enumerate()
+            .map(|(i, station)| station.service(&format!("stations[{i}]")))
+            .collect()
+    }
+
+    /// Builds the network of stations, with the warm-up of the run set.
+    pub fn build<R>(&self) -> Result<Pipeline<Arrivals, R>, ScenarioError>
+    where
+        R: Rng + SeedableRng + fmt::Debug + Send + 'static,
+    {
+        let params = PipelineParams {
+            arrival_distr: self.arrivals()?,
+            seed: self.seed(),
+        };
+        let nodes = self
+            .services()?
+            .into_iter()
+            .map(|service| Box::new(service) as Box<dyn IntoPipelineNode<R>>)
+            .collect();
+
+        let mut pipeline = params
+            .build_network(self.network(nodes)?)
+            .map_err(ScenarioError::network)?;
+        if let Some(warm_up) = self.run.warm_up() {
+            pipeline.set_warm_up(Time::new(warm_up).map_err(|err| ScenarioError::Invalid {
+                field: "run.warm_up".to_owned(),
+                message: err.to_string(),
+            })?);
+        }
+
+        Ok(pipeline)
+    }
+
+    /// The line as a tandem network, to compare the simulation with the theory, `None` if
+    /// the arrivals aren't a renewal process or the stations don't make a line.
+    pub fn tandem(&self) -> Result<Option<TandemNetwork>, ScenarioError> {
+        let ArrivalConfig::Renewal(arrival) = &self.arrival else {
+            return Ok(None);
+        };
+        if !self.is_line() {
+            return Ok(None);
+        }
+
+        let arrival_distr = arrival
+            .build()
+            .map_err(|err| ScenarioError::invalid("arrival", err))?;
+        Ok(Some(TandemNetwork::new(
+            &arrival_distr,
+            self.services()?.iter().map(Station::from).collect(),
+        )))
+    }
+
+    /// The line as a Jackson network, to compare the simulation with the theory when the buffers
+    /// are unlimited, `None` if the arrivals aren't Poisson or the stations don't make a line.
+    pub fn jackson(&self) -> Result<Option<JacksonNetwork>, ScenarioError> {
+        let ArrivalConfig::Renewal(arrival) = &self.arrival else {
+            return Ok(None);
+        };
+        if !self.is_line() {
+            return Ok(None);
+        }
+
+        let arrival_distr = arrival
+            .build()
+            .map_err(|err| ScenarioError::invalid("arrival", err))?;
+        Ok(JacksonNetwork::line(
+            &arrival_distr,
+            self.services()?.iter().map(Station::from).collect(),
+        ))
+    }
+
+    pub fn runner(&self) -> Result<ReplicationRunner, ScenarioError> {
+        let arrival_rate = if self.run.control_variate {
+            Some(self.arrival.rate().ok_or_else(|| ScenarioError::Invalid {
+                field: "run.control_variate".to_owned(),
+                message: "needs arrivals of a known rate, e.g. not a trace".to_owned(),
+            })?)
+        } else {
+            None
+        };
+
+        Ok(ReplicationRunner {
+            working_time: Time::new(self.run.working_time).map_err(|err| {
+                ScenarioError::Invalid {
+                    field: "run.working_time".to_owned(),
+                    message: err.to_string(),
+                }
+            })?,
+            level: self.run.level,
+            replications: self.run.replications,
+            precision: self.run.precision,
+            max_replications: self.run.max_replications,
+            antithetic: self.run.antithetic,
+            arrival_rate,
+        })
+    }
+}
+
+impl StationConfig {
+    fn service(&self, field: &str) -> Result<ServiceParams<PerClass<Distr>>, ScenarioError> {
+        if self.servers == 0 {
+            return Err(ScenarioError::Invalid {
+                field: format!("{field}.servers"),
+                message: "a station needs at least one server".to_owned(),
+            });
+        }
+
+        let handling_time_distribution = self
+            .handling_time
+            .build(&format!("{field}.handling_time"))?;
+
+        Ok(ServiceParams {
+            buffer_size: self.buffer_size,
+            servers: self.servers,
+            discipline: self.discipline.queue(),
+            preemption: self.preemption,
+            handling_time_distribution,
+        })
+    }
+}
+
+impl WarmUpDetectionConfig {
+    pub fn detection(self) -> WarmUpDetection {
+        match self {
+            Self::Welch {
+                replications,
+                window,
+                tolerance,
+            } => WarmUpDetection::Welch {
+                replications,
+                window,
+                tolerance,
+            },
+            Self::Mser5 => WarmUpDetection::Mser5,
+        }
+    }
+}
+
+impl Discipline {
+    pub fn queue(self) -> Box<dyn QueueDiscipline> {
+        match self {
+            Self::Fifo => Box::<Fifo>::default(),
+            Self::Lifo => Box::<Lifo>::default(),
+            Self::Siro => Box::<Siro>::default(),
+            Self::Sjf => Box::<Sjf>::default(),
+            Self::Priority => Box::<Priority>::default(),
+        }
+    }
+}
+
+impl RunConfig {
+    /// The fixed warm-up or the detected one, which is known once the scenario is loaded.
+    pub fn warm_up(&self) -> Option<f64> {
+        match self.warm_up? {
+            WarmUpConfig::Fixed(warm_up) => Some(warm_up),
+            WarmUpConfig::Detected(_) => self.detected_warm_up,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ScenarioError> {
+        let invalid = |field: &str, message: String| ScenarioError::Invalid {
+            field: format!("run.{field}"),
+            message,
+        };
+
+        if !(self.working_time.is_finite() && self.working_time > 0.0) {
+            return Err(invalid(
+                "working_time",
+                format!("must be positive, got {}", self.working_time),
+            ));
+        }
+        match self.warm_up {
+            Some(WarmUpConfig::Fixed(warm_up))
+                if !(warm_up >= 0.0 && warm_up < self.working_time) =>
+            {
+                return Err(invalid(
+                    "warm_up",
+                    format!(
+                        "must be non-negative and less than the working time {}, got {warm_up}",
+                        self.working_time
+                    ),
+                ));
+            }
+            Some(WarmUpConfig::Detected(WarmUpDetectionConfig::Welch {
+                replications,
+                tolerance,
+                ..
+            })) => {
+                if replications == 0 {
+                    return Err(invalid(
+                        "warm_up.welch.replications",
+                        "must be positive".to_owned(),
+                    ));
+                }
+                if !(tolerance.is_finite() && tolerance >= 0.0) {
+                    return Err(invalid(
+                        "warm_up.welch.tolerance",
+                        format!("must be non-negative, got {tolerance}"),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        if !(self.level > 0.0 && self.level < 1.0) {
+            return Err(invalid(
+                "level",
+                format!("must be between 0 and 1, got {}", self.level),
+            ));
+        }
+        if self.max_replications < self.replications {
+            return Err(invalid(
+                "max_replications",
+                format!(
+                    "must not be less than the replications {}, got {}",
+                    self.replications, self.max_replications
+                ),
+            ));
+        }
+        if let Some(Precision::Absolute(value) | Precision::Relative(value)) = self.precision {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(invalid(
+                    "precision",
+                    format!("must be positive, got {value}"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ScenarioError {
+    /// The error of the parameter of the field, e.g. the `mean` of `arrival`.
+    fn invalid(field: &str, err: PipelineError) -> Self {
+        match err {
+            PipelineError::InvalidParameter { parameter, message } => Self::Invalid {
+                field: format!("{field}.{parameter}"),
+                message,
+            },
+            err => Self::Invalid {
+                field: field.to_owned(),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    /// The error of linking the nodes, the other errors of building the network come from
+    /// the stations.
+    fn network(err: PipelineError) -> Self {
+        match err {
+            PipelineError::InvalidNetwork(message) => Self::Network(message),
+            err => Self::invalid("stations", err),
+        }
+    }
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {source}", path.display()),
+            Self::UnsupportedFormat(path) => write!(
+                f,
+                "unsupported scenario format of {}, expected .toml or .json",
+                path.display()
+            ),
+            Self::Parse(message) => write!(f, "failed to parse scenario: {message}"),
+            Self::Invalid { field, message } => write!(f, "invalid `{field}`: {message}"),
+            Self::Network(message) => write!(
+                f,
+                "invalid network: {message}, the nodes are numbered in the order of the stations, \
+                 the routers, the forks and the joins"
+            ),
+            Self::Run(err) => write!(f, "the simulation failed: {err}"),
+        }
+    }
+}
+
+impl From<PipelineError> for ScenarioError {
+    fn from(err: PipelineError) -> Self {
+        Self::Run(err)
+    }
+}
+
+impl std::error::Error for ScenarioError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Run(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+fn one() -> usize {
+    1
+}
@@ -0,0 +1,304 @@
+use pipeline::{
+    network::{BlockingPolicy, Network, NodeId, Target},
+    node::IntoPipelineNode,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{Scenario, ScenarioError};
+
+/// Node a request goes to, e.g. `"exit"` or `{ station = 1 }`, the nodes of every kind are
+/// numbered by their position in the scenario.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TargetConfig {
+    #[default]
+    Exit,
+    Station(usize),
+    Router(usize),
+    Fork(usize),
+    Join(usize),
+}
+
+/// What a station does with a handled request when the next node is full, e.g. `"drop"`
+/// or `{ reroute = { station = 2 } }`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum BlockingConfig {
+    #[default]
+    Bas,
+    Bbs,
+    Drop,
+    Reroute(TargetConfig),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouterConfig {
+    /// The probabilities are normalized by their sum.
+    pub routes: Vec<RouteConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    pub to: TargetConfig,
+    pub probability: f64,
+}
+
+/// Sends a part of the request down every branch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ForkConfig {
+    pub branches: Vec<TargetConfig>,
+}
+
+/// Waits for all `parts` of a forked request and passes it on as a whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JoinConfig {
+    pub parts: usize,
+    #[serde(default)]
+    pub next: TargetConfig,
+}
+
+/// Ids of the nodes of the network, by kind.
+struct NodeIds {
+    stations: Vec<NodeId>,
+    routers: Vec<NodeId>,
+    forks: Vec<NodeId>,
+    joins: Vec<NodeId>,
+}
+
+impl Scenario {
+    /// Whether the stations make a line with BAS blocking, the network of the tandem theory.
+    pub fn is_line(&self) -> bool {
+        self.entry.unwrap_or(TargetConfig::Station(0)) == TargetConfig::Station(0)
+            && self.routers.is_empty()
+            && self.forks.is_empty()
+            && self.joins.is_empty()
+            && self.stations.iter().enumerate().all(|(i, station)| {
+                station.blocking == BlockingConfig::Bas
+                    && station.next.unwrap_or_else(|| self.default_next(i)) == self.default_next(i)
+            })
+    }
+
+    /// The station after the `i`-th one, the last one leads to the exit.
+    fn default_next(&self, i: usize) -> TargetConfig {
+        if i + 1 < self.stations.len() {
+            TargetConfig::Station(i + 1)
+        } else {
+            TargetConfig::Exit
+        }
+    }
+
+    /// Links the nodes as the scenario describes, the stations come first in the network,
+    /// followed by the routers, the forks and the joins.
+    pub(super) fn network<R: Rng>(
+        &self,
+        nodes: Vec<Box<dyn IntoPipelineNode<R>>>,
+    ) -> Result<Network<R>, ScenarioError> {
+        let mut network = Network::new();
+        let ids = NodeIds {
+            stations: nodes
+                .into_iter()
+                .map(|node| network.add_node(node))
+                .collect(),
+            routers: self.routers.iter().map(|_| network.add_router()).collect(),
+            forks: self.forks.iter().map(|_| network.add_fork()).collect(),
+            joins: self
+                .joins
+                .iter()
+                .map(|join| network.add_join(join.parts))
+                .collect(),
+        };
+        if let Some(entry) = self.entry {
+            network.set_entry(ids.target(entry, "entry")?);
+        }
+        for (i, station) in self.stations.iter().enumerate() {
+            let field = format!("stations[{i}]");
+            let next = station.next.unwrap_or_else(|| self.default_next(i));
+            network
+                .connect(ids.stations[i], ids.target(next, &format!("{field}.next"))?)
+                .map_err(ScenarioError::network)?;
+
+            let policy = match station.blocking {
+                BlockingConfig::Bas => BlockingPolicy::Bas,
+                BlockingConfig::Bbs => BlockingPolicy::Bbs,
+                BlockingConfig::Drop => BlockingPolicy::Drop,
+                BlockingConfig::Reroute(target) => BlockingPolicy::Reroute(
+                    ids.target(target, &format!("{field}.blocking.reroute"))?,
+                ),
+            };
+            network
+                .set_blocking_policy(ids.stations[i], policy)
+                .map_err(ScenarioError::network)?;
+        }
+        for (i, router) in self.routers.iter().enumerate() {
+            if router.routes.is_empty() {
+                return Err(ScenarioError::Invalid {
+                    field: format!("routers[{i}].routes"),
+                    message: "a router needs at least one route".to_owned(),
+                });
+            }
+
+            for (j, route) in router.routes.iter().enumerate() {
+                let field = format!("routers[{i}].routes[{j}]");
+                network
+                    .route(
+                        ids.routers[i],
+                        ids.target(route.to, &format!("{field}.to"))?,
+                        route.probability,
+                    )
+                    .map_err(|err| ScenarioError::invalid(&field, err))?;
+            }
+        }
+        for (i, fork) in self.forks.iter().enumerate() {
+            for (j, branch) in fork.branches.iter().enumerate() {
+                let to = ids.target(*branch, &format!("forks[{i}].branches[{j}]"))?;
+                network
+                    .connect(ids.forks[i], to)
+                    .map_err(ScenarioError::network)?;
+            }
+        }
+        for (i, join) in self.joins.iter().enumerate() {
+            let next = ids.target(join.next, &format!("joins[{i}].next"))?;
+            network
+                .connect(ids.joins[i], next)
+                .map_err(ScenarioError::network)?;
+        }
+
+        Ok(network)
+    }
+}
+
+impl NodeIds {
+    fn target(&self, target: TargetConfig, field: &str) -> Result<Target, ScenarioError> {
+        let (ids, kind, i) = match target {
+            TargetConfig::Exit => return Ok(Target::Exit),
+            TargetConfig::Station(i) => (&self.stations, "stations", i),
+            TargetConfig::Router(i) => (&self.routers, "routers", i),
+            TargetConfig::Fork(i) => (&self.forks, "forks", i),
+            TargetConfig::Join(i) => (&self.joins, "joins", i),
+        };
+
+        ids.get(i)
+            .map(|id| Target::Node(*id))
+            .ok_or_else(|| ScenarioError::Invalid {
+                field: field.to_owned(),
+                message: format!(
+                    "there is no {kind}[{i}], the scenario has {} {kind}",
+                    ids.len()
+                ),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pipeline::{Request, Statistics, Time};
+    use rand::rngs::StdRng;
+
+    fn time(value: f64) -> Time {
+        Time::new(value).unwrap()
+    }
+
+    /// Runs a request every `1` through a station handling it in `0.25` and a station handling
+    /// it in `2`, neither has a buffer, so the second one is full every other arrival.
+    fn run(blocking: &str) -> (Statistics, Vec<Request>) {
+        let scenario = Scenario::from_toml(&format!(
+            r#"
+            seed = 1
+            arrival = {{ type = "deterministic", value = 1.0 }}
+
+            [[stations]]
+            buffer_size = 0
+            handling_time = {{ type = "deterministic", value = 0.25 }}
+            blocking = {blocking}
+
+            [[stations]]
+            buffer_size = 0
+            handling_time = {{ type = "deterministic", value = 2.0 }}
+            "#
+        ))
+        .unwrap();
+        let mut pipeline = scenario.build::<StdRng>().unwrap();
+        let mut requests = pipeline.work_during(time(10.0)).unwrap();
+        requests.sort_by_key(|request| request.id);
+
+        (pipeline.get_statistics(), requests)
+    }
+
+    fn ids(requests: &[Request]) -> Vec<usize> {
+        requests.iter().map(|request| request.id).collect()
+    }
+
+    #[test]
+    fn bas_holds_the_handled_request() {
+        let (statistics, requests) = run(r#""bas""#);
+
+        // the requests 2, 4, 6 and 8 wait a unit each for the second station
+        assert_eq!(statistics.nodes[0].blocked_time, 4.0);
+        assert_eq!(statistics.nodes[0].lost_requests_count, 0.0);
+        // the odd ones after the first come while the first station holds a request
+        assert_eq!(statistics.delayed_requests_count, 4.0);
+        assert_eq!(ids(&requests), [1, 2, 4, 6]);
+
+        let visit = requests[1].visits[0];
+        assert_eq!(visit.finished_at, Some(time(2.25)));
+        assert_eq!(visit.left_at, Some(time(3.25)));
+    }
+
+    #[test]
+    fn bbs_postpones_the_handling() {
+        let (statistics, requests) = run(r#""bbs""#);
+
+        assert_eq!(statistics.nodes[0].blocked_time, 0.0);
+        assert_eq!(statistics.nodes[0].lost_requests_count, 0.0);
+        assert_eq!(statistics.delayed_requests_count, 5.0);
+        assert_eq!(ids(&requests), [1, 2, 4, 6]);
+
+        // the request 2 starts when the second station lets the first one go
+        let visit = requests[1].visits[0];
+        assert_eq!(visit.started_at, Some(time(3.25)));
+        assert_eq!(visit.left_at, Some(time(3.5)));
+    }
+
+    #[test]
+    fn drop_loses_the_handled_request() {
+        let (statistics, requests) = run(r#""drop""#);
+
+        assert_eq!(statistics.nodes[0].blocked_time, 0.0);
+        assert_eq!(statistics.nodes[0].lost_requests_count, 4.0);
+        assert_eq!(statistics.classes[0].lost_requests_count, 4.0);
+        assert_eq!(statistics.delayed_requests_count, 0.0);
+        assert_eq!(ids(&requests), [1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn reroute_sends_the_handled_request_past_the_full_station() {
+        let (statistics, requests) = run(r#"{ reroute = "exit" }"#);
+
+        assert_eq!(statistics.nodes[0].blocked_time, 0.0);
+        assert_eq!(statistics.nodes[0].lost_requests_count, 0.0);
+        assert_eq!(statistics.delayed_requests_count, 0.0);
+        assert_eq!(ids(&requests), [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // the even requests come while the second station is busy and leave after the first one
+        let stations = requests[0]
+            .visits
+            .iter()
+            .map(|visit| visit.node)
+            .collect::<Vec<_>>();
+        for request in &requests {
+            let nodes = request
+                .visits
+                .iter()
+                .map(|visit| visit.node)
+                .collect::<Vec<_>>();
+            let visited = if request.id % 2 == 0 { 1 } else { 2 };
+            assert_eq!(nodes, stations[..visited]);
+        }
+    }
+}
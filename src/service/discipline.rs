@@ -0,0 +1,292 @@
+use super::task::Task;
+use pipeline::Request;
+use rand::{Rng, RngCore};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+};
+
+/// Decides the order the buffered tasks are handled in.
+pub trait QueueDiscipline: Debug + Send {
+    fn name(&self) -> &'static str;
+
+    fn push(&mut self, task: Task);
+
+    /// Returns a task interrupted by a higher priority one to the buffer.
+    fn requeue(&mut self, task: Task) {
+        self.push(task);
+    }
+
+    fn pop(&mut self, rng: &mut dyn RngCore) -> Option<Task>;
+
+    fn len(&self) -> usize;
+
+    fn clear(&mut self);
+}
+
+/// First in, first out.
+#[derive(Debug, Default)]
+pub struct Fifo(VecDeque<Task>);
+
+/// Last in, first out.
+#[derive(Debug, Default)]
+pub struct Lifo(VecDeque<Task>);
+
+/// Service in random order.
+#[derive(Debug, Default)]
+pub struct Siro(Vec<Task>);
+
+/// Shortest job first, the handling time is known since the task is sampled on admission.
+#[derive(Debug, Default)]
+pub struct Sjf(Vec<Task>);
+
+/// Static priority classes, the lower class is handled first and the tasks of a class are handled in FIFO order.
+#[derive(Debug)]
+pub struct Priority {
+    class_of: fn(&Request) -> usize,
+    classes: BTreeMap<usize, VecDeque<Task>>,
+}
+
+impl Priority {
+    /// Creates the discipline with the classes given by `class_of`.
+    pub fn new(class_of: fn(&Request) -> usize) -> Self {
+        Self {
+            class_of,
+            classes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Classes are the priorities of the requests.
+impl Default for Priority {
+    fn default() -> Self {
+        Self::new(|request| request.priority)
+    }
+}
+
+impl QueueDiscipline for Fifo {
+    fn name(&self) -> &'static str {
+        "FIFO"
+    }
+
+    fn push(&mut self, task: Task) {
+        self.0.push_back(task);
+    }
+
+    fn requeue(&mut self, task: Task) {
+        self.0.push_front(task);
+    }
+
+    fn pop(&mut self, _: &mut dyn RngCore) -> Option<Task> {
+        self.0.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl QueueDiscipline for Lifo {
+    fn name(&self) -> &'static str {
+        "LIFO"
+    }
+
+    fn push(&mut self, task: Task) {
+        self.0.push_back(task);
+    }
+
+    fn pop(&mut self, _: &mut dyn RngCore) -> Option<Task> {
+        self.0.pop_back()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl QueueDiscipline for Siro {
+    fn name(&self) -> &'static str {
+        "SIRO"
+    }
+
+    fn push(&mut self, task: Task) {
+        self.0.push(task);
+    }
+
+    fn pop(&mut self, rng: &mut dyn RngCore) -> Option<Task> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let i = rng.gen_range(0..self.0.len());
+        Some(self.0.swap_remove(i))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl QueueDiscipline for Sjf {
+    fn name(&self) -> &'static str {
+        "SJF"
+    }
+
+    fn push(&mut self, task: Task) {
+        self.0.push(task);
+    }
+
+    fn pop(&mut self, _: &mut dyn RngCore) -> Option<Task> {
+        let (i, _) = self.0.iter().enumerate().min_by(|(_, a), (_, b)| {
+            f64::from(a.handling_time).total_cmp(&f64::from(b.handling_time))
+        })?;
+
+        Some(self.0.remove(i))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl QueueDiscipline for Priority {
+    fn name(&self) -> &'static str {
+        "priority"
+    }
+
+    fn push(&mut self, task: Task) {
+        let class = (self.class_of)(&task.request);
+        self.classes.entry(class).or_default().push_back(task);
+    }
+
+    fn requeue(&mut self, task: Task) {
+        let class = (self.class_of)(&task.request);
+        self.classes.entry(class).or_default().push_front(task);
+    }
+
+    fn pop(&mut self, _: &mut dyn RngCore) -> Option<Task> {
+        let mut class = self.classes.first_entry()?;
+        let task = class.get_mut().pop_front();
+
+        if class.get().is_empty() {
+            class.remove();
+        }
+
+        task
+    }
+
+    fn len(&self) -> usize {
+        self.classes.values().map(VecDeque::len).sum()
+    }
+
+    fn clear(&mut self) {
+        self.classes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pipeline::Time;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Task of the request `id` with the priority and the handling time.
+    fn task(id: usize, priority: usize, handling_time: f64) -> Task {
+        Task {
+            request: Request {
+                id,
+                priority,
+                ..Request::default()
+            },
+            handling_time: Time::new(handling_time).unwrap(),
+            started_at: Time::ZERO,
+            entered_at: Time::ZERO,
+        }
+    }
+
+    /// Ids of the requests in the order the discipline pops them.
+    fn pop_order(discipline: &mut dyn QueueDiscipline, tasks: Vec<Task>) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(1);
+        tasks.into_iter().for_each(|task| discipline.push(task));
+        assert_eq!(discipline.len(), 4);
+
+        let order = std::iter::from_fn(|| discipline.pop(&mut rng))
+            .map(|task| task.request.id)
+            .collect();
+        assert_eq!(discipline.len(), 0);
+
+        order
+    }
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            task(0, 1, 3.0),
+            task(1, 0, 2.0),
+            task(2, 1, 1.0),
+            task(3, 0, 2.0),
+        ]
+    }
+
+    #[test]
+    fn fifo_pops_in_arrival_order() {
+        assert_eq!(pop_order(&mut Fifo::default(), tasks()), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn lifo_pops_the_latest_first() {
+        assert_eq!(pop_order(&mut Lifo::default(), tasks()), [3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn siro_pops_every_task_once() {
+        let mut order = pop_order(&mut Siro::default(), tasks());
+        order.sort_unstable();
+
+        assert_eq!(order, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn sjf_pops_the_shortest_first() {
+        // the tasks 1 and 3 are equally long, the earlier one goes first
+        assert_eq!(pop_order(&mut Sjf::default(), tasks()), [2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn priority_breaks_ties_in_arrival_order() {
+        assert_eq!(pop_order(&mut Priority::default(), tasks()), [1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn requeued_tasks_go_first_within_their_class() {
+        let mut fifo = Fifo::default();
+        fifo.push(task(0, 0, 1.0));
+        fifo.requeue(task(1, 0, 1.0));
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(fifo.pop(&mut rng).map(|task| task.request.id), Some(1));
+
+        let mut priority = Priority::default();
+        priority.push(task(0, 1, 1.0));
+        priority.push(task(1, 0, 1.0));
+        priority.requeue(task(2, 1, 1.0));
+        let order = std::iter::from_fn(|| priority.pop(&mut rng))
+            .map(|task| task.request.id)
+            .collect::<Vec<_>>();
+        assert_eq!(order, [1, 2, 0]);
+    }
+}
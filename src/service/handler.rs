@@ -1,18 +1,20 @@
-use rand::{prelude::Distribution, Rng};
+use rand::Rng;
 
-use super::task::{HandlingState, Task};
+use super::{
+    discipline::QueueDiscipline, handling_time::HandlingTimeDistribution, task::Task, Preemption,
+};
 use pipeline::{Request, Time};
-use std::{collections::VecDeque, ops::ControlFlow};
 
 #[derive(Debug)]
 pub(super) struct Handler<R, D>
 where
     R: Rng,
-    D: Distribution<Time>,
+    D: HandlingTimeDistribution,
 {
-    buffer: VecDeque<Request>,
-    buffer_size: usize,
-    current_task: Option<Task>,
+    buffer: Box<dyn QueueDiscipline>,
+    /// `None` if the buffer is unlimited.
+    buffer_size: Option<usize>,
+    tasks: Vec<Option<Task>>,
     rng: R,
     distr: D,
 }
@@ -23,69 +25,139 @@ pub(super) struct FullBufferError;
 impl<R, D> Handler<R, D>
 where
     R: Rng,
-    D: Distribution<Time>,
+    D: HandlingTimeDistribution,
 {
-    pub fn new(buffer_size: usize, rng: R, distr: D) -> Self {
+    pub fn new(
+        buffer: Box<dyn QueueDiscipline>,
+        buffer_size: Option<usize>,
+        servers: usize,
+        rng: R,
+        distr: D,
+    ) -> Self {
         Self {
-            buffer: VecDeque::with_capacity(buffer_size),
+            buffer,
             buffer_size,
-            current_task: None,
+            tasks: vec![None; servers],
             rng,
             distr,
         }
     }
 
+    pub fn set_rng(&mut self, rng: R) {
+        self.rng = rng;
+    }
+
     pub fn clear(&mut self) {
         self.buffer.clear();
-        self.current_task.take();
+        self.tasks.iter_mut().for_each(|task| *task = None);
     }
 
-    pub fn add(&mut self, request: Request) -> Result<(), FullBufferError> {
-        if self.buffer.len() == self.buffer_size {
-            Err(FullBufferError)
-        } else {
-            Ok(self.buffer.push_back(request))
-        }
+    /// Whether the buffer has no space for a request, the free servers take requests from the buffer
+    /// right away, so the requests taken by them don't need the buffer space.
+    pub fn is_full(&self, free_servers: usize) -> bool {
+        self.buffer_size
+            .is_some_and(|buffer_size| self.buffer.len() >= buffer_size + free_servers)
     }
 
-    pub fn progress_during(&mut self, delta_time: &mut Time) -> ControlFlow<(), Option<Request>> {
-        if *delta_time == Time::ZERO {
-            return ControlFlow::Break(());
+    /// Buffers the request, its handling time is sampled right away so the discipline can rely on it.
+    pub fn add(
+        &mut self,
+        mut request: Request,
+        free_servers: usize,
+        now: Time,
+    ) -> Result<(), FullBufferError> {
+        if self.is_full(free_servers) {
+            return Err(FullBufferError);
         }
 
-        let (waiting_time, maybe_request) = match self.get_task()?.handle_during(delta_time) {
-            HandlingState::Completed {
-                request,
-                waiting_time,
-            } => (waiting_time, Some(request)),
-            HandlingState::InProgress { task, waiting_time } => {
-                self.current_task = Some(task);
-                (waiting_time, None)
-            }
-        };
+        let handling_time = self.handling_time(&mut request);
+        self.buffer.push(Task {
+            request,
+            handling_time,
+            started_at: Time::ZERO,
+            entered_at: now,
+        });
 
-        for i in 0..self.buffer.len() {
-            self.buffer[i].leaving_time += waiting_time;
-        }
+        Ok(())
+    }
 
-        ControlFlow::Continue(maybe_request)
+    pub fn discipline(&self) -> &'static str {
+        self.buffer.name()
     }
 
-    fn get_task(&mut self) -> ControlFlow<(), Task> {
-        self.current_task
-            .take()
-            .or_else(|| self.create_task())
-            .map_or(ControlFlow::Break(()), |task| ControlFlow::Continue(task))
+    pub fn is_busy(&self, server: usize) -> bool {
+        self.tasks[server].is_some()
+    }
+
+    pub fn task_priority(&self, server: usize) -> Option<usize> {
+        self.tasks[server]
+            .as_ref()
+            .map(|task| task.request.priority)
+    }
+
+    /// Takes the next request from the buffer to the server and returns the time it will be handled in.
+    pub fn start_task(&mut self, server: usize, now: Time) -> Option<Time> {
+        let mut task = self.buffer.pop(&mut self.rng)?;
+        task.started_at = now;
+        task.request.start_service(now);
+        let handling_time = task.handling_time;
+        self.tasks[server] = Some(task);
+
+        Some(handling_time)
     }
 
-    fn create_task(&mut self) -> Option<Task> {
-        let request = self.buffer.pop_front()?;
-        let handling_time = self.distr.sample(&mut self.rng);
+    /// Returns the task of the server to the buffer and starts handling the request instead of it,
+    /// the buffer must have free space for the interrupted task.
+    pub fn preempt(
+        &mut self,
+        server: usize,
+        mut request: Request,
+        now: Time,
+        preemption: Preemption,
+    ) -> Time {
+        if let Some(task) = self.tasks[server].take() {
+            let mut task = task.interrupt(now);
+
+            if let Preemption::Repeat = preemption {
+                task.handling_time = self.distr.sample_for(&task.request, &mut self.rng);
+            }
+
+            self.buffer.requeue(task);
+        }
 
-        Some(Task {
+        let handling_time = self.handling_time(&mut request);
+        request.start_service(now);
+        self.tasks[server] = Some(Task {
             request,
             handling_time,
-        })
+            started_at: now,
+            entered_at: now,
+        });
+
+        handling_time
+    }
+
+    pub fn complete_task(&mut self, server: usize, now: Time) -> Option<Task> {
+        let mut task = self.tasks[server].take()?;
+        task.request.finish_service(now);
+
+        Some(task)
+    }
+
+    pub fn queue_length(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn busy_servers(&self) -> usize {
+        self.tasks.iter().filter(|task| task.is_some()).count()
+    }
+
+    /// Takes the handling time the request came with, so only the first station uses it.
+    fn handling_time(&mut self, request: &mut Request) -> Time {
+        request
+            .service_time
+            .take()
+            .unwrap_or_else(|| self.distr.sample_for(request, &mut self.rng))
     }
 }
 
@@ -96,3 +168,62 @@ impl std::fmt::Display for FullBufferError {
 }
 
 impl std::error::Error for FullBufferError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::discipline::Fifo;
+    use pipeline::distr::DeterministicDistr;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn time(value: f64) -> Time {
+        Time::new(value).unwrap()
+    }
+
+    fn request(id: usize, priority: usize) -> Request {
+        Request {
+            id,
+            priority,
+            ..Request::default()
+        }
+    }
+
+    /// Handling time of the low priority request after a high priority one has interrupted it
+    /// at `2` of its `5` and has been handled for `5` itself.
+    fn time_after_preemption(preemption: Preemption) -> Time {
+        let mut handler = Handler::new(
+            Box::<Fifo>::default(),
+            Some(1),
+            1,
+            StdRng::seed_from_u64(1),
+            DeterministicDistr::new(5.0).unwrap(),
+        );
+
+        handler.add(request(0, 1), 1, Time::ZERO).unwrap();
+        assert_eq!(handler.start_task(0, Time::ZERO), Some(time(5.0)));
+
+        let handling_time = handler.preempt(0, request(1, 0), time(2.0), preemption);
+        assert_eq!(handling_time, time(5.0));
+        assert_eq!(handler.queue_length(), 1);
+        assert_eq!(handler.task_priority(0), Some(0));
+
+        let task = handler.complete_task(0, time(7.0)).unwrap();
+        assert_eq!(task.request.id, 1);
+
+        let handling_time = handler.start_task(0, time(7.0)).unwrap();
+        let task = handler.complete_task(0, time(7.0)).unwrap();
+        assert_eq!(task.request.id, 0);
+
+        handling_time
+    }
+
+    #[test]
+    fn resumed_task_finishes_its_remaining_time() {
+        assert_eq!(time_after_preemption(Preemption::Resume), time(3.0));
+    }
+
+    #[test]
+    fn repeated_task_restarts_its_full_handling() {
+        assert_eq!(time_after_preemption(Preemption::Repeat), time(5.0));
+    }
+}
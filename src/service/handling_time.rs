@@ -0,0 +1,61 @@
+use pipeline::{distr::Moments, PipelineError, Request, Time};
+use rand::{prelude::Distribution, Rng};
+
+/// Samples the time a request is handled in.
+pub trait HandlingTimeDistribution {
+    fn sample_for<R: Rng + ?Sized>(&self, request: &Request, rng: &mut R) -> Time;
+}
+
+impl<D> HandlingTimeDistribution for D
+where
+    D: Distribution<Time>,
+{
+    fn sample_for<R: Rng + ?Sized>(&self, _: &Request, rng: &mut R) -> Time {
+        self.sample(rng)
+    }
+}
+
+/// A distribution for each request class, the classes past the end use the last distribution.
+#[derive(Debug)]
+pub struct PerClass<D>(Vec<D>)
+where
+    D: Distribution<Time>;
+
+impl<D> PerClass<D>
+where
+    D: Distribution<Time>,
+{
+    pub fn new(distributions: Vec<D>) -> Result<Self, PipelineError> {
+        if distributions.is_empty() {
+            return Err(PipelineError::invalid_parameter(
+                "distributions",
+                "at least one handling time distribution is required",
+            ));
+        }
+
+        Ok(Self(distributions))
+    }
+}
+
+impl<D> HandlingTimeDistribution for PerClass<D>
+where
+    D: Distribution<Time>,
+{
+    fn sample_for<R: Rng + ?Sized>(&self, request: &Request, rng: &mut R) -> Time {
+        self.0[request.class.min(self.0.len() - 1)].sample(rng)
+    }
+}
+
+/// The moments of class `0`, the class of every request of a single-class arrival process.
+impl<D> Moments for PerClass<D>
+where
+    D: Distribution<Time> + Moments,
+{
+    fn mean(&self) -> f64 {
+        self.0[0].mean()
+    }
+
+    fn variance(&self) -> f64 {
+        self.0[0].variance()
+    }
+}
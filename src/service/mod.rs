@@ -1,132 +1,491 @@
+pub mod discipline;
 mod handler;
+pub mod handling_time;
+mod occupancy;
 mod task;
 
-use std::{fmt::Debug, ops::ControlFlow};
+use std::fmt::Debug;
 
-use self::handler::Handler;
+use self::{
+    discipline::QueueDiscipline,
+    handler::Handler,
+    handling_time::HandlingTimeDistribution,
+    occupancy::{Occupancy, OccupancyStatistics},
+};
 use pipeline::{
-    node::{BlockedError, IntoPipelineNode, PipelineNode, PipelineNodeStatistics},
-    Request, Time,
+    analytic::Station,
+    distr::Moments,
+    node::{
+        BlockedError, IntoPipelineNode, NodeContext, PipelineNode, PipelineNodeStatistics,
+        ServerStatistics,
+    },
+    quantile::QuantileSketch,
+    streams::Antithetic,
+    PipelineError, Request, Time, Visit,
 };
 
-use rand::{prelude::Distribution, Rng};
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub struct ServiceParams<D>
 where
-    D: Distribution<Time>,
+    D: HandlingTimeDistribution,
 {
-    pub buffer_size: usize,
+    /// `None` if the buffer is unlimited.
+    pub buffer_size: Option<usize>,
+    pub servers: usize,
+    pub discipline: Box<dyn QueueDiscipline>,
+    pub preemption: Preemption,
     pub handling_time_distribution: D,
 }
 
+/// What happens when a request arrives while every server handles a lower priority request.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preemption {
+    /// The request waits in the buffer.
+    #[default]
+    NonPreemptive,
+    /// The request interrupts the lowest priority task, which continues from where it's stopped later.
+    Resume,
+    /// The request interrupts the lowest priority task, which is handled from the start with
+    /// a resampled handling time later.
+    Repeat,
+}
+
 #[derive(Debug)]
 pub(crate) struct Service<R, D>
 where
     R: Rng,
-    D: Distribution<Time>,
+    D: HandlingTimeDistribution,
 {
-    state: State,
+    servers: Vec<Server>,
     idle_time: Time,
-    handler: Handler<R, D>,
-    next: Box<dyn PipelineNode>,
+    idle_since: Option<Time>,
+    /// Time during which some server was holding a request the next node couldn't accept.
+    blocked_time: Time,
+    blocked_since: Option<Time>,
+    statistics_since: Time,
+    preemption: Preemption,
+    preemptions_count: usize,
+    handled_requests_count: usize,
+    /// Total time the handled requests have spent in the station.
+    sojourn_time: Time,
+    /// Times from entering the station until starting to be handled of the handled requests.
+    waiting_times: QuantileSketch,
+    occupancy: Occupancy,
+    last_tag: usize,
+    handler: Handler<Antithetic<R>, D>,
+}
+
+#[derive(Debug)]
+struct Server {
+    /// Tag of the completion of the server's current task.
+    tag: usize,
+    state: State,
+    busy_time: Time,
+    busy_since: Option<Time>,
+    blocked_time: Time,
+    blocked_since: Option<Time>,
+    /// When the request the server holds after handling it has entered the station.
+    entered_at: Time,
 }
 
 #[derive(Debug)]
 enum State {
-    Blocked { stuck_request: Request },
+    Blocked,
     Active,
 }
 
+/// The station a config describes, to compute the theoretical metrics for it.
+impl<D> From<&ServiceParams<D>> for Station
+where
+    D: HandlingTimeDistribution + Moments,
+{
+    fn from(params: &ServiceParams<D>) -> Self {
+        Station::new(
+            &params.handling_time_distribution,
+            params.servers,
+            params
+                .buffer_size
+                .map(|buffer_size| buffer_size + params.servers),
+        )
+    }
+}
+
 impl<R, D> IntoPipelineNode<R> for ServiceParams<D>
 where
-    R: Rng + Debug + 'static,
-    D: Distribution<Time> + Debug + 'static,
+    R: Rng + SeedableRng + Debug + Send + 'static,
+    D: HandlingTimeDistribution + Debug + Send + 'static,
 {
-    fn into_node(
-        self: Box<Self>,
-        rand_gen: R,
-        next: Box<dyn PipelineNode>,
-    ) -> Box<dyn PipelineNode> {
-        let handler = Handler::new(self.buffer_size, rand_gen, self.handling_time_distribution);
-
-        Box::new(Service {
-            next,
+    fn into_node(self: Box<Self>, rand_gen: R) -> Result<Box<dyn PipelineNode>, PipelineError> {
+        if self.servers == 0 {
+            return Err(PipelineError::invalid_parameter(
+                "servers",
+                "a station needs at least one server",
+            ));
+        }
+
+        let handler = Handler::new(
+            self.discipline,
+            self.buffer_size,
+            self.servers,
+            Antithetic::new(rand_gen, false),
+            self.handling_time_distribution,
+        );
+
+        Ok(Box::new(Service {
             handler,
-            state: State::Active,
+            servers: (0..self.servers).map(|_| Server::new()).collect(),
             idle_time: Time::ZERO,
-        })
+            idle_since: Some(Time::ZERO),
+            blocked_time: Time::ZERO,
+            blocked_since: None,
+            statistics_since: Time::ZERO,
+            preemption: self.preemption,
+            preemptions_count: 0,
+            handled_requests_count: 0,
+            sojourn_time: Time::ZERO,
+            waiting_times: QuantileSketch::default(),
+            occupancy: Occupancy::default(),
+            last_tag: 0,
+        }))
     }
 }
 
 impl<R, D> PipelineNode for Service<R, D>
 where
-    R: Rng + Debug,
-    D: Distribution<Time> + Debug,
+    R: Rng + SeedableRng + Debug + Send,
+    D: HandlingTimeDistribution + Debug + Send,
 {
     fn push_request(
         &mut self,
-        delta_time: &mut Time,
+        ctx: &mut NodeContext,
         request: Request,
     ) -> Result<(), BlockedError> {
-        if let State::Blocked { stuck_request } = self.state {
-            if let Err(_) = self.next.push_request(delta_time, stuck_request) {
-                self.idle_time += *delta_time;
-                return Err(BlockedError);
+        if self.handler.is_full(self.free_servers()) {
+            return Err(BlockedError);
+        }
+
+        if let Some(server) = self.preemptible_server(&request) {
+            let handling_time = self
+                .handler
+                .preempt(server, request, ctx.now(), self.preemption);
+            self.preemptions_count += 1;
+            self.start(ctx, server, handling_time);
+            self.observe(ctx.now());
+
+            return Ok(());
+        }
+
+        self.handler
+            .add(request, self.free_servers(), ctx.now())
+            .map_err(|_| BlockedError)?;
+        self.handle_next(ctx);
+        self.observe(ctx.now());
+
+        Ok(())
+    }
+
+    fn complete(&mut self, ctx: &mut NodeContext, tag: usize) -> Option<Request> {
+        // completions of the preempted tasks don't match any server
+        let server = self.server_by_tag(tag)?;
+        let task = self.handler.complete_task(server, ctx.now())?;
+
+        if let Some(waiting_time) = task.request.visits.last().and_then(Visit::waiting_time) {
+            self.waiting_times.push(f64::from(waiting_time));
+        }
+
+        let server = &mut self.servers[server];
+        server.state = State::Blocked;
+        server.entered_at = task.entered_at;
+        server.blocked_since = Some(ctx.now());
+        if let Some(busy_since) = server.busy_since.take() {
+            server.busy_time = server
+                .busy_time
+                .saturating_add(ctx.now().saturating_sub(busy_since));
+        }
+
+        if self
+            .servers
+            .iter()
+            .all(|server| server.busy_since.is_none())
+        {
+            self.idle_since = Some(ctx.now());
+        }
+
+        if self.blocked_since.is_none() {
+            self.blocked_since = Some(ctx.now());
+        }
+
+        self.observe(ctx.now());
+
+        Some(task.request)
+    }
+
+    fn unblock(&mut self, ctx: &mut NodeContext, tag: usize) {
+        if let Some(server) = self.server_by_tag(tag) {
+            let server = &mut self.servers[server];
+            server.state = State::Active;
+            if let Some(blocked_since) = server.blocked_since.take() {
+                server.blocked_time = server
+                    .blocked_time
+                    .saturating_add(ctx.now().saturating_sub(blocked_since));
             }
 
-            self.state = State::Active;
+            self.handled_requests_count += 1;
+            self.sojourn_time = self
+                .sojourn_time
+                .saturating_add(ctx.now().saturating_sub(server.entered_at));
+        }
+
+        if self
+            .servers
+            .iter()
+            .all(|server| server.blocked_since.is_none())
+        {
+            if let Some(blocked_since) = self.blocked_since.take() {
+                self.blocked_time = self
+                    .blocked_time
+                    .saturating_add(ctx.now().saturating_sub(blocked_since));
+            }
         }
 
-        let handle_res = self.handle_requests(delta_time);
-        self.idle_time += *delta_time;
-        handle_res?;
+        self.handle_next(ctx);
+        self.observe(ctx.now());
+    }
+
+    fn resume(&mut self, ctx: &mut NodeContext) {
+        self.handle_next(ctx);
+        self.observe(ctx.now());
+    }
 
-        self.handler.add(request).map_err(|_| BlockedError)
+    fn can_accept(&self) -> bool {
+        !self.handler.is_full(self.free_servers())
     }
 
-    fn get_statistics(&self) -> Vec<PipelineNodeStatistics> {
-        let mut statistics = vec![PipelineNodeStatistics {
-            idle_time: f32::from(self.idle_time),
-        }];
-        statistics.append(&mut self.next.get_statistics());
+    fn get_statistics(&self, now: Time) -> PipelineNodeStatistics {
+        let idle_time = match self.idle_since {
+            Some(idle_since) => self
+                .idle_time
+                .saturating_add(now.saturating_sub(idle_since)),
+            None => self.idle_time,
+        };
+        let blocked_time = match self.blocked_since {
+            Some(blocked_since) => self
+                .blocked_time
+                .saturating_add(now.saturating_sub(blocked_since)),
+            None => self.blocked_time,
+        };
 
-        statistics
+        let observed_time = f64::from(now) - f64::from(self.statistics_since);
+        let servers = self
+            .servers
+            .iter()
+            .map(|server| server.get_statistics(now, self.statistics_since))
+            .collect::<Vec<_>>();
+        let busy_time = servers.iter().map(|server| server.busy_time).sum::<f64>();
+
+        let OccupancyStatistics {
+            average_requests_number,
+            average_queue_length,
+            max_queue_length,
+            occupancy,
+        } = self.occupancy.get_statistics(now, self.statistics_since);
+
+        PipelineNodeStatistics {
+            idle_time: f64::from(idle_time),
+            blocked_time: f64::from(blocked_time),
+            discipline: self.handler.discipline(),
+            preemptions_count: self.preemptions_count as f64,
+            average_requests_number,
+            average_queue_length,
+            max_queue_length,
+            occupancy,
+            utilization: (observed_time > 0.0)
+                .then(|| busy_time / (servers.len() as f64 * observed_time)),
+            handled_requests_count: self.handled_requests_count as f64,
+            throughput: (observed_time > 0.0)
+                .then(|| self.handled_requests_count as f64 / observed_time),
+            average_sojourn_time: (self.handled_requests_count > 0)
+                .then(|| f64::from(self.sojourn_time) / self.handled_requests_count as f64),
+            waiting_time_quantiles: self.waiting_times.quantiles(),
+            waiting_time_cdf: self.waiting_times.cdf(),
+            servers,
+            // the lost requests and the blocked arrivals are counted by the pipeline
+            ..Default::default()
+        }
     }
 
     fn reset(&mut self) {
         self.handler.clear();
-        self.next.reset();
+        self.servers
+            .iter_mut()
+            .for_each(|server| *server = Server::new());
+        self.idle_time = Time::ZERO;
+        self.idle_since = Some(Time::ZERO);
+        self.blocked_time = Time::ZERO;
+        self.blocked_since = None;
+        self.statistics_since = Time::ZERO;
+        self.preemptions_count = 0;
+        self.handled_requests_count = 0;
+        self.sojourn_time = Time::ZERO;
+        self.waiting_times.clear();
+        self.occupancy = Occupancy::default();
+    }
+
+    fn reset_statistics(&mut self, now: Time) {
+        self.servers.iter_mut().for_each(|server| {
+            server.busy_time = Time::ZERO;
+            server.busy_since = server.busy_since.map(|_| now);
+            server.blocked_time = Time::ZERO;
+            server.blocked_since = server.blocked_since.map(|_| now);
+        });
         self.idle_time = Time::ZERO;
+        self.idle_since = self.idle_since.map(|_| now);
+        self.blocked_time = Time::ZERO;
+        self.blocked_since = self.blocked_since.map(|_| now);
+        self.statistics_since = now;
+        self.preemptions_count = 0;
+        self.handled_requests_count = 0;
+        self.sojourn_time = Time::ZERO;
+        self.waiting_times.clear();
+        self.occupancy.reset_statistics(now);
+    }
+
+    fn reseed(&mut self, seed: u64, antithetic: bool) {
+        self.handler
+            .set_rng(Antithetic::new(R::seed_from_u64(seed), antithetic));
     }
 }
 
 impl<R, D> Service<R, D>
 where
     R: Rng,
-    D: Distribution<Time>,
+    D: HandlingTimeDistribution,
 {
-    fn handle_requests(&mut self, delta_time: &mut Time) -> Result<(), BlockedError> {
-        let mut last_delta_time = *delta_time;
-
-        loop {
-            let request = match self.handler.progress_during(delta_time) {
-                ControlFlow::Continue(Some(request)) => request,
-                ControlFlow::Continue(None) => continue,
-                ControlFlow::Break(()) => break Ok(()),
+    /// Starts handling buffered requests on every free server.
+    fn handle_next(&mut self, ctx: &mut NodeContext) {
+        if !ctx.can_start() {
+            return;
+        }
+
+        for i in 0..self.servers.len() {
+            if matches!(self.servers[i].state, State::Blocked) || self.handler.is_busy(i) {
+                continue;
+            }
+
+            let Some(handling_time) = self.handler.start_task(i, ctx.now()) else {
+                return;
             };
+            self.start(ctx, i, handling_time);
+        }
+    }
 
-            if let err @ Err(BlockedError) = self
-                .next
-                .push_request(&mut (last_delta_time - *delta_time), request)
-            {
-                self.state = State::Blocked {
-                    stuck_request: request,
-                };
-                break err;
+    /// Schedules the completion of the task the server has just started.
+    fn start(&mut self, ctx: &mut NodeContext, server: usize, handling_time: Time) {
+        self.last_tag += 1;
+
+        let server = &mut self.servers[server];
+        server.tag = self.last_tag;
+        ctx.schedule_completion(handling_time, server.tag);
+
+        if server.busy_since.is_none() {
+            server.busy_since = Some(ctx.now());
+        }
+
+        if let Some(idle_since) = self.idle_since.take() {
+            self.idle_time = self
+                .idle_time
+                .saturating_add(ctx.now().saturating_sub(idle_since));
+        }
+    }
+
+    /// Finds the server with the lowest priority task that the request can preempt,
+    /// nothing is preempted while some server is free.
+    fn preemptible_server(&self, request: &Request) -> Option<usize> {
+        if let Preemption::NonPreemptive = self.preemption {
+            return None;
+        }
+
+        let mut preemptible: Option<(usize, usize)> = None;
+
+        for (i, server) in self.servers.iter().enumerate() {
+            if let State::Blocked = server.state {
+                continue;
+            }
+
+            let priority = self.handler.task_priority(i)?;
+            if priority > request.priority && preemptible.is_none_or(|(_, p)| priority > p) {
+                preemptible = Some((i, priority));
             }
+        }
+
+        preemptible.map(|(i, _)| i)
+    }
+
+    fn free_servers(&self) -> usize {
+        (0..self.servers.len())
+            .filter(|&i| matches!(self.servers[i].state, State::Active) && !self.handler.is_busy(i))
+            .count()
+    }
+
+    fn server_by_tag(&self, tag: usize) -> Option<usize> {
+        self.servers.iter().position(|server| server.tag == tag)
+    }
+
+    /// Records the number of requests in the station after it has changed.
+    fn observe(&mut self, now: Time) {
+        let blocked = self
+            .servers
+            .iter()
+            .filter(|server| matches!(server.state, State::Blocked))
+            .count();
+        let queue_length = self.handler.queue_length();
+
+        self.occupancy.update(
+            now,
+            queue_length + self.handler.busy_servers() + blocked,
+            queue_length,
+        );
+    }
+}
+
+impl Server {
+    fn new() -> Self {
+        Self {
+            tag: 0,
+            state: State::Active,
+            busy_time: Time::ZERO,
+            busy_since: None,
+            blocked_time: Time::ZERO,
+            blocked_since: None,
+            entered_at: Time::ZERO,
+        }
+    }
+
+    fn get_statistics(&self, now: Time, since: Time) -> ServerStatistics {
+        let busy_time = match self.busy_since {
+            Some(busy_since) => self
+                .busy_time
+                .saturating_add(now.saturating_sub(busy_since)),
+            None => self.busy_time,
+        };
+
+        let blocked_time = match self.blocked_since {
+            Some(blocked_since) => self
+                .blocked_time
+                .saturating_add(now.saturating_sub(blocked_since)),
+            None => self.blocked_time,
+        };
 
-            last_delta_time = *delta_time;
+        ServerStatistics {
+            busy_time: f64::from(busy_time),
+            blocked_time: f64::from(blocked_time),
+            idle_time: f64::from(now.saturating_sub(since))
+                - f64::from(busy_time)
+                - f64::from(blocked_time),
         }
     }
 }
@@ -0,0 +1,84 @@
+use pipeline::Time;
+
+/// Time-weighted record of the number of requests in a station.
+#[derive(Debug, Default)]
+pub(super) struct Occupancy {
+    last_change: Time,
+    requests_number: usize,
+    queue_length: usize,
+    max_queue_length: usize,
+    /// Time spent with each number of requests in the station.
+    time_by_requests_number: Vec<f64>,
+    queue_length_area: f64,
+}
+
+pub(super) struct OccupancyStatistics {
+    pub average_requests_number: Option<f64>,
+    pub average_queue_length: Option<f64>,
+    pub max_queue_length: f64,
+    pub occupancy: Vec<f64>,
+}
+
+impl Occupancy {
+    /// Records the numbers of requests the station has had since the last change and sets the new ones.
+    pub fn update(&mut self, now: Time, requests_number: usize, queue_length: usize) {
+        let elapsed = f64::from(now) - f64::from(self.last_change);
+
+        if self.time_by_requests_number.len() <= self.requests_number {
+            self.time_by_requests_number
+                .resize(self.requests_number + 1, 0.0);
+        }
+        self.time_by_requests_number[self.requests_number] += elapsed;
+        self.queue_length_area += self.queue_length as f64 * elapsed;
+
+        self.last_change = now;
+        self.requests_number = requests_number;
+        self.queue_length = queue_length;
+        self.max_queue_length = self.max_queue_length.max(queue_length);
+    }
+
+    /// Forgets the recorded time but keeps the current numbers of requests.
+    pub fn reset_statistics(&mut self, now: Time) {
+        self.last_change = now;
+        self.max_queue_length = self.queue_length;
+        self.time_by_requests_number.clear();
+        self.queue_length_area = 0.0;
+    }
+
+    /// The averages are `None` and the occupancy is empty if no time has been observed.
+    pub fn get_statistics(&self, now: Time, since: Time) -> OccupancyStatistics {
+        let elapsed = f64::from(now) - f64::from(self.last_change);
+        let observed_time = f64::from(now) - f64::from(since);
+
+        if observed_time <= 0.0 {
+            return OccupancyStatistics {
+                average_requests_number: None,
+                average_queue_length: None,
+                max_queue_length: self.max_queue_length as f64,
+                occupancy: Vec::new(),
+            };
+        }
+
+        let mut occupancy = self.time_by_requests_number.clone();
+        if occupancy.len() <= self.requests_number {
+            occupancy.resize(self.requests_number + 1, 0.0);
+        }
+        occupancy[self.requests_number] += elapsed;
+        occupancy.iter_mut().for_each(|time| *time /= observed_time);
+
+        OccupancyStatistics {
+            average_requests_number: Some(
+                occupancy
+                    .iter()
+                    .enumerate()
+                    .map(|(n, p)| n as f64 * p)
+                    .sum(),
+            ),
+            average_queue_length: Some(
+                (self.queue_length_area + self.queue_length as f64 * elapsed) / observed_time,
+            ),
+            max_queue_length: self.max_queue_length as f64,
+            occupancy,
+        }
+    }
+}